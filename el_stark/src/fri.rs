@@ -1,24 +1,30 @@
+use std::marker::PhantomData;
+
 use blake2::Blake2b512;
-use field_math::field::{field::Field, field_element::FieldElement};
-use sha3::Digest;
+use field_math::{field::{batch_inverse, extension::ExtensionField, packed, powers::coset, traits::PrimeField}, poly::uni::Uni};
+use serde::{de::DeserializeOwned, Serialize};
+use sha3::{digest::{ExtendableOutput, Update, XofReader}, Digest, Shake256};
 
-use crate::proof_stream::ProofStream;
+use crate::proof_stream::{ProofError, ProofStream};
 use crate::merkle::Merkle;
 
 
-pub struct Fri<'a> {
-    offset: FieldElement<'a>,
-    omega: FieldElement<'a>,
-    field: &'a Field,
+/// FRI over the evaluation domain `offset * <omega>` in the base field `F`.
+/// Codewords start out in `F`; the folding challenges, and with them every
+/// folded codeword, live in the extension `E` (by default `F` itself).
+pub struct Fri<F: PrimeField, E: ExtensionField<F> = F> {
+    offset: F,
+    omega: F,
     domain_length: usize,
     expansion_factor: usize,
     num_colinearity_tests: usize,
+    _extension: PhantomData<E>,
 }
 
-impl<'a> Fri<'a> {
+impl<F: PrimeField, E: ExtensionField<F> + Serialize + DeserializeOwned> Fri<F, E> {
     pub fn new(
-        offset: FieldElement<'a>,
-        omega: FieldElement<'a>,
+        offset: F,
+        omega: F,
         initial_domain_length: usize,
         expansion_factor: usize,
         num_colinearity_tests: usize,
@@ -26,10 +32,10 @@ impl<'a> Fri<'a> {
         Fri {
             offset,
             omega,
-            field: omega.field,
             domain_length: initial_domain_length,
             expansion_factor,
             num_colinearity_tests,
+            _extension: PhantomData,
         }
     }
 
@@ -43,13 +49,11 @@ impl<'a> Fri<'a> {
         num_rounds
     }
 
-    pub fn eval_domain(&self) -> Vec<FieldElement> {
-        (0..self.domain_length)
-            .map(|i| self.offset.clone() * self.omega.pow(i as u32))
-            .collect()
+    pub fn eval_domain(&self) -> Vec<F> {
+        coset(self.offset, self.omega, self.domain_length).collect()
     }
 
-    pub fn prove(&self, codeword: Vec<FieldElement>, proof_stream: &mut ProofStream) -> Vec<usize> {
+    pub fn prove(&self, codeword: Vec<F>, proof_stream: &mut ProofStream) -> Vec<usize> {
         assert_eq!(self.domain_length, codeword.len(), "initial codeword length does not match length of initial codeword");
 
         // commit phase
@@ -68,43 +72,42 @@ impl<'a> Fri<'a> {
         top_level_indices
     }
 
-    pub fn commit(&self, mut codeword: Vec<FieldElement<'a>>, proof_stream: &mut ProofStream) -> Vec<Vec<FieldElement>> {
-        let one = self.field.one();
-        let two = FieldElement::from(2, self.field);
-        let mut omega = self.omega.clone();
-        let mut offset = self.offset.clone();
+    pub fn commit(&self, codeword: Vec<F>, proof_stream: &mut ProofStream) -> Vec<Vec<E>> {
+        let two_inverse = E::from_u64(2).inverse();
+        let mut codeword: Vec<E> = codeword.into_iter().map(E::from_base).collect();
+        let mut omega = self.omega;
+        let mut offset = self.offset;
         let mut codewords = Vec::new();
 
         for r in 0..self.num_rounds() {
-            let root = Merkle::commit(&codeword.iter().map(|x| x.to_bytes()).collect::<Vec<_>>());
+            let root = Merkle::commit_elements(&codeword);
             proof_stream.push(root);
 
             if r == self.num_rounds() - 1 {
                 break;
             }
 
-            let alpha = self.field.sample(proof_stream.prover_fiat_shamir(32));
+            let alpha: E = proof_stream.prover_sample();
 
             codewords.push(codeword.clone());
 
-            codeword = (0..codeword.len() / 2)
-                .map(|i| {
-                    // let i = i as i128;
-                    let i_i128 = i as i128;
-                    let i_u32: u32 = i.try_into().unwrap();
-                    let omega_i = omega.pow(i_u32);
-                    let alpha_omega_i = alpha / (offset.clone() * omega_i.clone());
-                    let alpha_omega_i_inv = alpha_omega_i.clone().inverse();
-                    let codeword_i = codeword[i].clone();
-                    let codeword_i_plus = one.clone() + alpha_omega_i.clone();
-                    let codeword_i_minus = one.clone() - alpha_omega_i.clone();
-                    let codeword_i_2 = codeword[codeword.len() / 2 + i].clone();
-                    let codeword_i_2_plus = codeword_i_plus.clone() * codeword_i.clone();
-                    let codeword_i_2_minus = codeword_i_minus.clone() * codeword_i_2.clone();
-                    let codeword_i_final = two.clone().inverse() * (codeword_i_2_plus + codeword_i_2_minus);
-                    codeword_i_final
-                })
-                .collect();
+            // with x = offset * omega^i, the folded value is
+            // (c_i + c_{half+i}) / 2 + alpha / (2x) * (c_i - c_{half+i});
+            // all of 1/x is inverted at once, and the rest runs on packed slices
+            let half = codeword.len() / 2;
+            let domain: Vec<F> = coset(offset, omega, half).collect();
+            let alpha_half = alpha * two_inverse;
+            let factors: Vec<E> = batch_inverse(&domain).into_iter().map(|x_inverse| alpha_half * x_inverse).collect();
+
+            let (low, high) = codeword.split_at(half);
+            let mut difference = low.to_vec();
+            packed::sub_assign(&mut difference, high);
+            packed::mul_assign(&mut difference, &factors);
+            let mut folded = low.to_vec();
+            packed::add_assign(&mut folded, high);
+            packed::scale_assign(&mut folded, two_inverse);
+            packed::add_assign(&mut folded, &difference);
+            codeword = folded;
             omega = omega.pow(2);
             offset = offset.pow(2);
         }
@@ -115,41 +118,49 @@ impl<'a> Fri<'a> {
         codewords
     }
 
-    pub fn query(&self, current_codeword: &Vec<FieldElement>, next_codeword: &Vec<FieldElement>, c_indices: &Vec<usize>, proof_stream: &mut ProofStream) -> Vec<usize> {
-        let a_indices = c_indices.clone();
+    pub fn query(&self, current_codeword: &[E], next_codeword: &[E], c_indices: &[usize], proof_stream: &mut ProofStream) -> Vec<usize> {
+        let a_indices = c_indices.to_vec();
         let b_indices: Vec<usize> = c_indices.iter().map(|index| index + current_codeword.len() / 2).collect();
 
         for s in 0..self.num_colinearity_tests {
-            proof_stream.push((current_codeword[a_indices[s]].clone(), current_codeword[b_indices[s]].clone(), next_codeword[c_indices[s]].clone()));
+            proof_stream.push((current_codeword[a_indices[s]], current_codeword[b_indices[s]], next_codeword[c_indices[s]]));
         }
 
         for s in 0..self.num_colinearity_tests {
-            proof_stream.push(Merkle::open(a_indices[s], &current_codeword.iter().map(|x| x.to_bytes()).collect::<Vec<_>>()));
-            proof_stream.push(Merkle::open(b_indices[s], &current_codeword.iter().map(|x| x.to_bytes()).collect::<Vec<_>>()));
-            proof_stream.push(Merkle::open(c_indices[s], &next_codeword.iter().map(|x| x.to_bytes()).collect::<Vec<_>>()));
+            proof_stream.push(Merkle::open_element(a_indices[s], current_codeword));
+            proof_stream.push(Merkle::open_element(b_indices[s], current_codeword));
+            proof_stream.push(Merkle::open_element(c_indices[s], next_codeword));
         }
 
         a_indices.iter().chain(b_indices.iter()).cloned().collect()
     }
 
-    pub fn sample_index(byte_array: Vec<u8>, size: usize) -> usize {
-        let mut acc = 0;
-        for b in byte_array {
-            acc = (acc << 8) ^ b as usize;
+    /// Uniform index in `0..size`. Candidates are drawn from a SHAKE256
+    /// stream over `byte_array` and rejected if they fall in the last,
+    /// incomplete multiple of `size`, so no index is favoured.
+    pub fn sample_index(byte_array: &[u8], size: usize) -> usize {
+        let size = size as u64;
+        let limit = u64::MAX - u64::MAX % size;
+        let mut reader = Shake256::default().chain(byte_array).finalize_xof();
+        loop {
+            let mut candidate = [0u8; 8];
+            reader.read(&mut candidate);
+            let candidate = u64::from_le_bytes(candidate);
+            if candidate < limit {
+                return (candidate % size) as usize;
+            }
         }
-        acc % size
     }
 
     pub fn sample_indices(&self, seed: Vec<u8>, size: usize, reduced_size: usize, number: usize) -> Vec<usize> {
         assert!(number <= 2 * reduced_size, "not enough entropy in indices wrt last codeword");
-        let error_msg = format!("cannot sample more indices than available in last codeword; requested: {}, available: {}", number, reduced_size);
-        assert!(number <= reduced_size, &error_msg);
+        assert!(number <= reduced_size, "cannot sample more indices than available in last codeword; requested: {}, available: {}", number, reduced_size);
 
         let mut indices = Vec::new();
         let mut reduced_indices = Vec::new();
-        let mut counter = 0;
+        let mut counter: u64 = 0;
         while indices.len() < number {
-            let index = Fri::sample_index(Blake2b512::digest(&(seed.clone() + &counter.to_be_bytes().to_vec())), size);
+            let index = Self::sample_index(&Blake2b512::digest([seed.as_slice(), &counter.to_be_bytes()].concat()), size);
             let reduced_index = index % reduced_size;
             counter += 1;
             if !reduced_indices.contains(&reduced_index) {
@@ -161,59 +172,77 @@ impl<'a> Fri<'a> {
         indices
     }
 
-    pub fn verify(&self, proof_stream: &mut ProofStream, polynomial_values: &mut Vec<(usize, FieldElement<'a>)>) -> bool {
-        let mut omega = self.omega.clone();
-        let mut offset = self.offset.clone();
+    /// `Ok(false)` if the proof fails a check, and an error if it can't even
+    /// be read, e.g. because it is truncated or holds a non-canonical field
+    /// element. A malformed proof never panics.
+    pub fn verify(&self, proof_stream: &mut ProofStream, polynomial_values: &mut Vec<(usize, E)>) -> Result<bool, ProofError> {
+        let mut omega = self.omega;
+        let mut offset = self.offset;
+
+        // a domain too small for a single round proves nothing
+        let num_rounds = self.num_rounds();
+        if num_rounds == 0 {
+            return Ok(false);
+        }
 
-        let mut roots = Vec::new();
+        let mut roots: Vec<Vec<u8>> = Vec::new();
         let mut alphas = Vec::new();
 
-        for _ in 0..self.num_rounds() {
-            roots.push(proof_stream.pull());
-            alphas.push(self.field.sample(proof_stream.verifier_fiat_shamir(32)));
+        for _ in 0..num_rounds {
+            roots.push(proof_stream.pull()?);
+            alphas.push(proof_stream.verifier_sample::<E>());
         }
 
-        let last_codeword: Vec<FieldElement> = proof_stream.pull();
+        let last_codeword: Vec<E> = proof_stream.pull_elements()?;
 
-        if roots.last().unwrap() != &Merkle::commit(&last_codeword.iter().map(|x| x.to_bytes()).collect::<Vec<_>>()) {
-            println!("last codeword is not well formed");
-            return false;
+        // checked before committing to it, which needs a power of two length
+        let length = self.domain_length >> (num_rounds - 1);
+        if last_codeword.len() != length {
+            return Ok(false);
         }
 
-        let degree = (last_codeword.len() / self.expansion_factor) - 1;
-        let mut last_omega = omega.clone();
-        let mut last_offset = offset.clone();
+        if roots[num_rounds - 1] != Merkle::commit_elements(&last_codeword) {
+            return Ok(false);
+        }
+
+        let degree = (length / self.expansion_factor) as i128 - 1;
+        let mut last_omega = omega;
+        let mut last_offset = offset;
 
-        for _ in 0..(self.num_rounds() - 1) {
+        for _ in 0..(num_rounds - 1) {
             last_omega = last_omega.pow(2);
             last_offset = last_offset.pow(2);
         }
 
-        assert!(last_omega.clone().inverse() == last_omega.clone().pow((last_codeword.len() - 1) as u32), "omega does not have right order");
+        // the interpolation below needs omega to have order exactly the length
+        match last_omega.checked_inverse() {
+            Ok(inverse) if length.is_power_of_two()
+                && inverse == last_omega.pow((length - 1) as u64)
+                && (length == 1 || last_omega.pow((length / 2) as u64) != F::one()) => {}
+            _ => return Ok(false),
+        }
 
-        let last_domain: Vec<FieldElement> = (0..last_codeword.len())
-            .map(|i| last_offset.clone() * last_omega.clone().pow(i as u32))
-            .collect();
+        let last_domain: Vec<E> = coset(last_offset, last_omega, length).map(E::from_base).collect();
 
-        let poly = Polynomial::interpolate_domain(&last_domain, &last_codeword);
+        let poly = Uni::interpolate_coset(E::from_base(last_offset), E::from_base(last_omega), last_codeword.clone());
 
-        assert!(poly.evaluate_domain(&last_domain) == last_codeword, "re-evaluated codeword does not match original!");
+        if poly.clone().evaluate_domain(last_domain) != last_codeword {
+            return Ok(false);
+        }
 
-        if poly.degree() > degree {
-            println!("last codeword does not correspond to polynomial of low enough degree");
-            println!("observed degree: {}", poly.degree());
-            println!("but should be: {}", degree);
-            return false;
+        // the last codeword must come from a polynomial of low enough degree
+        if poly.clone().degree() > degree {
+            return Ok(false);
         }
 
         let top_level_indices = self.sample_indices(
-            proof_stream.verifier_fiat_shamir(),
+            proof_stream.verifier_fiat_shamir(32),
             self.domain_length >> 1,
-            self.domain_length >> (self.num_rounds() - 1),
+            length,
             self.num_colinearity_tests,
         );
 
-        for r in 0..(self.num_rounds() - 1) {
+        for r in 0..(num_rounds - 1) {
             let c_indices: Vec<usize> = top_level_indices
                 .iter()
                 .map(|index| index % (self.domain_length >> (r + 1)))
@@ -230,41 +259,40 @@ impl<'a> Fri<'a> {
             let mut cc = Vec::new();
 
             for s in 0..self.num_colinearity_tests {
-                let (ay, by, cy) = proof_stream.pull();
+                let (ay, by, cy) = match proof_stream.pull_elements::<E>()?[..] {
+                    [ay, by, cy] => (ay, by, cy),
+                    _ => return Ok(false),
+                };
                 aa.push(ay);
                 bb.push(by);
                 cc.push(cy);
 
                 if r == 0 {
-                    polynomial_values.push((a_indices[s], ay.clone()));
-                    polynomial_values.push((b_indices[s], by.clone()));
+                    polynomial_values.push((a_indices[s], ay));
+                    polynomial_values.push((b_indices[s], by));
                 }
 
-                let ax = offset.clone() * omega.clone().pow(a_indices[s] as u32);
-                let bx = offset.clone() * omega.clone().pow(b_indices[s] as u32);
-                let cx = alphas[r].clone();
+                let ax = E::from_base(offset * omega.pow(a_indices[s] as u64));
+                let bx = E::from_base(offset * omega.pow(b_indices[s] as u64));
+                let cx = alphas[r];
 
-                if !test_colinearity(&[(ax, ay.clone()), (bx, by.clone()), (cx, cy.clone())]) {
-                    println!("colinearity check failure");
-                    return false;
+                if !Uni::test_colinearity(vec![(ax, ay), (bx, by), (cx, cy)]) {
+                    return Ok(false);
                 }
             }
 
             for i in 0..self.num_colinearity_tests {
-                let path = proof_stream.pull();
-                if !Merkle::verify(&roots[r], a_indices[i], path, aa[i].clone()) {
-                    println!("merkle authentication path verification fails for aa");
-                    return false;
+                let path: Vec<Vec<u8>> = proof_stream.pull()?;
+                if !Merkle::verify_element(&roots[r], a_indices[i], &path, aa[i]) {
+                    return Ok(false);
                 }
-                let path = proof_stream.pull();
-                if !Merkle::verify(&roots[r], b_indices[i], path, bb[i].clone()) {
-                    println!("merkle authentication path verification fails for bb");
-                    return false;
+                let path: Vec<Vec<u8>> = proof_stream.pull()?;
+                if !Merkle::verify_element(&roots[r], b_indices[i], &path, bb[i]) {
+                    return Ok(false);
                 }
-                let path = proof_stream.pull();
-                if !Merkle::verify(&roots[r + 1], c_indices[i], path, cc[i].clone()) {
-                    println!("merkle authentication path verification fails for cc");
-                    return false;
+                let path: Vec<Vec<u8>> = proof_stream.pull()?;
+                if !Merkle::verify_element(&roots[r + 1], c_indices[i], &path, cc[i]) {
+                    return Ok(false);
                 }
             }
 
@@ -272,6 +300,174 @@ impl<'a> Fri<'a> {
             offset = offset.pow(2);
         }
 
-        true
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use field_math::field::{error::FieldError, extension::Fp2, field_element::Goldilocks, monty31::{BabyBear, BabyBearQuartic}, traits::{FftField, FiniteField}, DEFAULT_PRIME};
+
+    use super::*;
+
+    const DOMAIN_LENGTH: usize = 256;
+    const EXPANSION_FACTOR: usize = 4;
+
+    fn fri() -> Fri<Goldilocks> {
+        extension_fri()
+    }
+
+    fn extension_fri<E: ExtensionField<Goldilocks> + Serialize + DeserializeOwned>() -> Fri<Goldilocks, E> {
+        Fri::new(Goldilocks::generator(), Goldilocks::primitive_nth_root(DOMAIN_LENGTH as u64), DOMAIN_LENGTH, EXPANSION_FACTOR, 4)
+    }
+
+    /// Proves the codeword of a polynomial of the given degree and verifies
+    /// the proof after a round trip through its serialization, checking the
+    /// revealed values against the codeword.
+    fn prove_and_verify<E: ExtensionField<Goldilocks> + Serialize + DeserializeOwned>(degree: usize) -> Result<bool, ProofError> {
+        let fri = extension_fri::<E>();
+        let poly = Uni::from(Goldilocks::new(3).powers().take(degree + 1).collect());
+        let codeword = poly.evaluate_domain(fri.eval_domain());
+        let mut proof_stream = ProofStream::new();
+        fri.prove(codeword.clone(), &mut proof_stream);
+
+        let mut polynomial_values = Vec::new();
+        let verdict = fri.verify(&mut ProofStream::deserialize(&proof_stream.serialize())?, &mut polynomial_values)?;
+        if verdict {
+            assert_eq!(polynomial_values.len(), 2 * fri.num_colinearity_tests);
+            for (index, value) in polynomial_values {
+                assert_eq!(value, E::from_base(codeword[index]));
+            }
+        }
+        Ok(verdict)
+    }
+
+    #[test]
+    fn test_prove_and_verify() {
+        assert_eq!(prove_and_verify::<Goldilocks>(DOMAIN_LENGTH / EXPANSION_FACTOR - 1), Ok(true));
+        assert_eq!(prove_and_verify::<Goldilocks>(DOMAIN_LENGTH / EXPANSION_FACTOR), Ok(false));
+        assert_eq!(prove_and_verify::<Goldilocks>(DOMAIN_LENGTH - 1), Ok(false));
+    }
+
+    #[test]
+    fn test_prove_and_verify_over_extension() {
+        assert_eq!(prove_and_verify::<Fp2<Goldilocks>>(DOMAIN_LENGTH / EXPANSION_FACTOR - 1), Ok(true));
+        assert_eq!(prove_and_verify::<Fp2<Goldilocks>>(DOMAIN_LENGTH - 1), Ok(false));
+    }
+
+    /// The objects of an honest proof for a polynomial of low enough degree.
+    fn proof_objects() -> Vec<Vec<u8>> {
+        let poly = Uni::from(Goldilocks::new(3).powers().take(DOMAIN_LENGTH / EXPANSION_FACTOR).collect());
+        let mut proof_stream = ProofStream::new();
+        fri().prove(poly.evaluate_domain(fri().eval_domain()), &mut proof_stream);
+        serde_pickle::from_slice(&proof_stream.serialize(), Default::default()).unwrap()
+    }
+
+    fn verify(objects: &[Vec<u8>]) -> Result<bool, ProofError> {
+        let bytes = serde_pickle::to_vec(&objects, Default::default()).unwrap();
+        fri().verify(&mut ProofStream::deserialize(&bytes)?, &mut Vec::new())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_baby_bear() {
+        let fri = Fri::<BabyBear, BabyBearQuartic>::new(BabyBear::generator(), BabyBear::primitive_nth_root(DOMAIN_LENGTH as u64), DOMAIN_LENGTH, EXPANSION_FACTOR, 4);
+        for (degree, expected) in [(DOMAIN_LENGTH / EXPANSION_FACTOR - 1, Ok(true)), (DOMAIN_LENGTH - 1, Ok(false))] {
+            let poly = Uni::from(BabyBear::new(3).powers().take(degree + 1).collect());
+            let mut proof_stream = ProofStream::new();
+            fri.prove(poly.evaluate_domain(fri.eval_domain()), &mut proof_stream);
+            let mut proof_stream = ProofStream::deserialize(&proof_stream.serialize()).unwrap();
+            assert_eq!(fri.verify(&mut proof_stream, &mut Vec::new()), expected, "degree {}", degree);
+        }
+
+        let leafs = BabyBearQuartic::sample_many(b"leafs", 16);
+        let root = Merkle::commit_elements(&leafs);
+        for (index, &leaf) in leafs.iter().enumerate() {
+            let path = Merkle::open_element(index, &leafs);
+            assert!(Merkle::verify_element(&root, index, &path, leaf));
+            assert!(!Merkle::verify_element(&root, index, &path, leaf + BabyBearQuartic::one()));
+        }
+    }
+
+    #[test]
+    fn test_truncated_proof() {
+        let objects = proof_objects();
+        assert_eq!(verify(&objects), Ok(true));
+        for count in 0..objects.len() {
+            assert_eq!(verify(&objects[..count]), Err(ProofError::Exhausted), "{} objects", count);
+        }
+
+        let bytes = serde_pickle::to_vec(&objects, Default::default()).unwrap();
+        assert!(matches!(ProofStream::deserialize(&bytes[..bytes.len() / 2]), Err(ProofError::Malformed(_))));
+    }
+
+    #[test]
+    fn test_tampered_proof() {
+        let objects = proof_objects();
+        let garbage = serde_pickle::to_vec(&"garbage", Default::default()).unwrap();
+        for i in 0..objects.len() {
+            let mut tampered = objects.clone();
+            tampered[i] = garbage.clone();
+            assert!(matches!(verify(&tampered), Err(ProofError::Malformed(_))), "object {}", i);
+        }
+
+        // a well-formed last codeword that doesn't match its commitment
+        let mut tampered = objects.clone();
+        let num_rounds = fri().num_rounds();
+        let mut last_codeword: Vec<Goldilocks> = serde_pickle::from_slice(&objects[num_rounds], Default::default()).unwrap();
+        last_codeword[0] = last_codeword[0] + Goldilocks::one();
+        tampered[num_rounds] = serde_pickle::to_vec(&last_codeword, Default::default()).unwrap();
+        assert_eq!(verify(&tampered), Ok(false));
+    }
+
+    /// Serializes like a field element, whatever the bytes.
+    struct Encoding([u8; 8]);
+
+    impl Serialize for Encoding {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_bytes(&self.0)
+        }
+    }
+
+    #[test]
+    fn test_non_canonical_element() {
+        let objects = proof_objects();
+        let num_rounds = fri().num_rounds();
+        let bytes = serde_pickle::to_vec(&objects[..num_rounds].to_vec(), Default::default()).unwrap();
+        let mut proof_stream = ProofStream::deserialize(&bytes).unwrap();
+        // p itself, an unreduced encoding of zero, in place of the last codeword
+        let mut last_codeword: Vec<Encoding> = (0..DOMAIN_LENGTH >> (num_rounds - 1)).map(|_| Encoding(0u64.to_le_bytes())).collect();
+        last_codeword[7] = Encoding(DEFAULT_PRIME.to_le_bytes());
+        proof_stream.push(last_codeword);
+        assert_eq!(fri().verify(&mut proof_stream, &mut Vec::new()), Err(ProofError::Field(FieldError::NonCanonical)));
+    }
+
+    #[test]
+    fn test_oversized_element() {
+        let mut proof_stream = ProofStream::new();
+        proof_stream.push(vec![vec![0u8; 1 << 16]]);
+        assert!(matches!(proof_stream.pull_elements::<Goldilocks>(), Err(ProofError::Malformed(_))));
+    }
+
+    #[test]
+    fn test_wrong_last_codeword_length() {
+        let mut objects = proof_objects();
+        let num_rounds = fri().num_rounds();
+        for length in [0, 1, 31, 33] {
+            objects[num_rounds] = serde_pickle::to_vec(&vec![Goldilocks::one(); length], Default::default()).unwrap();
+            assert_eq!(verify(&objects), Ok(false), "length {}", length);
+        }
+    }
+
+    #[test]
+    fn test_wrong_merkle_paths() {
+        let leafs: Vec<Goldilocks> = Goldilocks::new(5).powers().take(8).collect();
+        let root = Merkle::commit_elements(&leafs);
+        let path = Merkle::open_element(3, &leafs);
+        assert!(Merkle::verify_element(&root, 3, &path, leafs[3]));
+        assert!(!Merkle::verify_element(&root, 3, &[], leafs[3]));
+        assert!(!Merkle::verify_element(&root, 3, &path[..1], leafs[3]));
+        assert!(!Merkle::verify_element(&root, 11, &path, leafs[3]));
+        assert!(!Merkle::verify_element(&root, usize::MAX, &path, leafs[3]));
+        assert!(!Merkle::verify_element(&root, 3, &[path.clone(), path.clone()].concat(), leafs[3]));
+    }
+}
@@ -0,0 +1,3 @@
+pub mod proof_stream;
+pub mod merkle;
+pub mod fri;
@@ -1,7 +1,3 @@
-mod proof_stream;
-mod merkle;
-mod fri;
-
 fn main() {
     println!("Hello, world!");
 }
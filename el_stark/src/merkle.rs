@@ -1,4 +1,5 @@
 use blake2::{Blake2b512, Digest};
+use field_math::field::traits::FiniteField;
 
 pub struct Merkle;
 
@@ -7,12 +8,12 @@ impl Merkle {
         assert!(leafs.len() & (leafs.len() - 1) == 0, "length must be power of two");
 
         if leafs.len() == 1 {
-            return leafs[0].clone();
+            leafs[0].clone()
         } else {
             let mid = leafs.len() / 2;
             let left_commit = Merkle::commit(&leafs[..mid]);
             let right_commit = Merkle::commit(&leafs[mid..]);
-            return Blake2b512::digest(&[left_commit, right_commit].concat()).to_vec();
+            Blake2b512::digest([left_commit, right_commit].concat()).to_vec()
         }
     }
 
@@ -21,37 +22,57 @@ impl Merkle {
         assert!(index < leafs.len(), "cannot open invalid index");
 
         if leafs.len() == 2 {
-            return vec![leafs[1 - index].clone()];
+            vec![leafs[1 - index].clone()]
         } else {
             let mid = leafs.len() / 2;
             if index < mid {
                 let mut path = Merkle::open(index, &leafs[..mid]);
                 path.push(Merkle::commit(&leafs[mid..]));
-                return path;
+                path
             } else {
                 let mut path = Merkle::open(index - mid, &leafs[mid..]);
                 path.push(Merkle::commit(&leafs[..mid]));
-                return path;
+                path
             }
         }
     }
 
+    /// False, rather than a panic, for any path that doesn't authenticate
+    /// `leaf`, including an empty one or one too short to reach `index`.
     pub fn verify(root: &[u8], index: usize, path: &[Vec<u8>], leaf: &[u8]) -> bool {
-        assert!(index < (1 << path.len()), "cannot verify invalid index");
+        if path.is_empty() || index.checked_shr(path.len() as u32).unwrap_or(0) != 0 {
+            return false;
+        }
 
         if path.len() == 1 {
             if index == 0 {
-                return root == &*Blake2b512::digest(&[leaf, &path[0]].concat()).to_vec();
+                root == &*Blake2b512::digest([leaf, &path[0]].concat()).to_vec()
             } else {
-                return root == &*Blake2b512::digest(&[&path[0], leaf].concat()).to_vec();
+                root == &*Blake2b512::digest([&path[0], leaf].concat()).to_vec()
             }
         } else {
-            let new_leaf = if index % 2 == 0 {
-                Blake2b512::digest(&[leaf, &path[0]].concat()).to_vec()
+            let new_leaf = if index.is_multiple_of(2) {
+                Blake2b512::digest([leaf, &path[0]].concat()).to_vec()
             } else {
-                Blake2b512::digest(&[&path[0], leaf].concat()).to_vec()
+                Blake2b512::digest([&path[0], leaf].concat()).to_vec()
             };
-            return Merkle::verify(root, index >> 1, &path[1..], &new_leaf);
+            Merkle::verify(root, index >> 1, &path[1..], &new_leaf)
         }
     }
-}
\ No newline at end of file
+
+    pub fn commit_elements<F: FiniteField>(leafs: &[F]) -> Vec<u8> {
+        Merkle::commit(&Merkle::leaf_bytes(leafs))
+    }
+
+    pub fn open_element<F: FiniteField>(index: usize, leafs: &[F]) -> Vec<Vec<u8>> {
+        Merkle::open(index, &Merkle::leaf_bytes(leafs))
+    }
+
+    pub fn verify_element<F: FiniteField>(root: &[u8], index: usize, path: &[Vec<u8>], leaf: F) -> bool {
+        Merkle::verify(root, index, path, &leaf.to_bytes())
+    }
+
+    fn leaf_bytes<F: FiniteField>(leafs: &[F]) -> Vec<Vec<u8>> {
+        leafs.iter().map(|leaf| leaf.to_bytes()).collect()
+    }
+}
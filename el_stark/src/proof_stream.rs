@@ -1,14 +1,93 @@
-use core::hash;
+use std::{fmt, marker::PhantomData};
 
-use serde::{Deserialize, Serialize};
+use field_math::field::{error::FieldError, traits::FiniteField};
+use serde::{de::{self, DeserializeOwned, SeqAccess, Visitor}, Deserialize, Deserializer, Serialize};
 use serde_pickle;
 use sha3::{Sha3_256, Digest};
 
+/// Why an object could not be read from a proof stream. Proofs come from
+/// an untrusted prover, so a verifier rejects on any of these rather than
+/// panicking.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProofError {
+    /// More objects were pulled than the stream holds.
+    Exhausted,
+    /// The stream or an object in it doesn't decode as the expected type.
+    Malformed(String),
+    /// A field element with an invalid encoding, e.g. one not reduced.
+    Field(FieldError),
+}
+
+impl fmt::Display for ProofError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProofError::Exhausted => write!(f, "cannot pull object; queue empty"),
+            ProofError::Malformed(message) => write!(f, "malformed proof object: {}", message),
+            ProofError::Field(error) => write!(f, "invalid field element: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for ProofError {}
+
+impl From<FieldError> for ProofError {
+    fn from(error: FieldError) -> Self {
+        ProofError::Field(error)
+    }
+}
+
+impl From<serde_pickle::Error> for ProofError {
+    fn from(error: serde_pickle::Error) -> Self {
+        ProofError::Malformed(error.to_string())
+    }
+}
+
+/// The encoding of one element of `F`, still to be checked by `from_bytes`.
+struct ElementBytes<F>(Vec<u8>, PhantomData<F>);
+
+impl<'de, F: FiniteField> Deserialize<'de> for ElementBytes<F> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BytesVisitor<F>(PhantomData<F>);
+
+        impl<'de, F: FiniteField> Visitor<'de> for BytesVisitor<F> {
+            type Value = ElementBytes<F>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(formatter, "a {}-byte field element encoding", F::BYTES)
+            }
+
+            fn visit_bytes<Err: de::Error>(self, bytes: &[u8]) -> Result<ElementBytes<F>, Err> {
+                Ok(ElementBytes(bytes.to_vec(), PhantomData))
+            }
+
+            // stop reading as soon as the sequence is too long for an element
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<ElementBytes<F>, A::Error> {
+                let mut bytes = Vec::with_capacity(F::BYTES);
+                while let Some(byte) = seq.next_element::<u8>()? {
+                    if bytes.len() == F::BYTES {
+                        return Err(de::Error::invalid_length(F::BYTES + 1, &self));
+                    }
+                    bytes.push(byte);
+                }
+                Ok(ElementBytes(bytes, PhantomData))
+            }
+        }
+
+        deserializer.deserialize_bytes(BytesVisitor(PhantomData))
+    }
+}
+
 pub struct ProofStream{
     objects : Vec<Vec<u8>>,
     read_index : usize
 }
 
+impl Default for ProofStream {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ProofStream{
     pub fn new() -> ProofStream{
         ProofStream{
@@ -17,33 +96,41 @@ impl ProofStream{
         }
     }
 
+    /// Field elements serialize as their canonical `to_bytes` encoding.
     pub fn push<T: Serialize>(&mut self, obj : T){
         let serialized_obj = serde_pickle::to_vec(&obj, Default::default()).unwrap();
         self.objects.push(serialized_obj);
     }
 
-    pub fn pull<T: for<'de> Deserialize<'de>>(&mut self) -> T {
-        assert!(self.read_index < self.objects.len(), "ProofStream: cannot pull object; queue empty.");
-        let serialized_obj = &self.objects[self.read_index];
+    pub fn pull<T: DeserializeOwned>(&mut self) -> Result<T, ProofError> {
+        let serialized_obj = self.objects.get(self.read_index).ok_or(ProofError::Exhausted)?;
         self.read_index += 1;
-        serde_pickle::from_slice(serialized_obj, Default::default()).unwrap()
+        Ok(serde_pickle::from_slice(serialized_obj, Default::default())?)
+    }
+
+    /// Pulls a sequence of field elements, such as a codeword. Unlike
+    /// `pull::<Vec<F>>`, an invalid encoding comes back as the `FieldError`
+    /// that `from_bytes` reports for it.
+    pub fn pull_elements<F: FiniteField>(&mut self) -> Result<Vec<F>, ProofError> {
+        let encodings: Vec<ElementBytes<F>> = self.pull()?;
+        Ok(encodings.iter().map(|bytes| F::from_bytes(&bytes.0)).collect::<Result<_, _>>()?)
     }
 
     pub fn serialize(&self) -> Vec<u8> {
         serde_pickle::to_vec(&self.objects, Default::default()).unwrap()
     }
 
-    pub fn deserialize(bb: &[u8]) -> Self {
-        let objects: Vec<Vec<u8>> = serde_pickle::from_slice(bb, Default::default()).unwrap();
-        ProofStream {
+    pub fn deserialize(bb: &[u8]) -> Result<Self, ProofError> {
+        let objects: Vec<Vec<u8>> = serde_pickle::from_slice(bb, Default::default())?;
+        Ok(ProofStream {
             objects,
             read_index: 0,
-        }
+        })
     }
 
     pub fn prover_fiat_shamir(&self, num_bytes: usize) -> Vec<u8> {
         let mut hasher = Sha3_256::default();        
-        hasher.update(&self.serialize());
+        hasher.update(self.serialize());
         hasher.finalize()[..num_bytes].to_vec()
     }
 
@@ -55,5 +142,13 @@ impl ProofStream{
         hasher.finalize()[..num_bytes].to_vec()
     }
 
+    /// Fiat-Shamir challenge drawn from any field, e.g. an extension of the
+    /// field the codewords live in.
+    pub fn prover_sample<E: FiniteField>(&self) -> E {
+        E::sample(&self.prover_fiat_shamir(32))
+    }
 
+    pub fn verifier_sample<E: FiniteField>(&self) -> E {
+        E::sample(&self.verifier_fiat_shamir(32))
+    }
 }
\ No newline at end of file
@@ -0,0 +1,620 @@
+//! Prime fields too large for a machine word, such as the STARK-252 field
+//! used by Starknet and the BN254 scalar field used by SNARKs. Elements are
+//! `N` little-endian 64-bit limbs in Montgomery form (`a * 2^(64N) mod p`),
+//! multiplied with the CIOS (coarsely integrated operand scanning) method.
+
+use std::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    ops::{Add, Div, Mul, Neg, Sub},
+    str::FromStr,
+};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+
+use super::{
+    error::FieldError,
+    hash_to_field::{hash_to_field, DEFAULT_DST},
+    serialization::deserialize_canonical,
+    traits::{FftField, FiniteField, PrimeField},
+};
+
+/// The constants defining a `BigPrimeField`.
+pub trait BigPrimeConfig<const N: usize>: Send + Sync + 'static {
+    /// The prime, as little-endian limbs. Its top bit must be clear, which
+    /// keeps sums within `N` limbs.
+    const MODULUS: [u64; N];
+
+    /// A generator of the multiplicative group. Unlike for 64-bit fields,
+    /// `p - 1` can't be factored on the fly to search for one.
+    const GENERATOR: u64;
+}
+
+/// An element of the prime field described by `C`, in Montgomery form.
+pub struct BigPrimeField<C, const N: usize> {
+    limbs: [u64; N],
+    _config: PhantomData<fn() -> C>,
+}
+
+/// `p = 2^251 + 17 * 2^192 + 1`, the Starknet field.
+pub struct Stark252Config;
+
+impl BigPrimeConfig<4> for Stark252Config {
+    const MODULUS: [u64; 4] = [1, 0, 0, 0x0800_0000_0000_0011];
+    const GENERATOR: u64 = 3;
+}
+
+pub type Stark252 = BigPrimeField<Stark252Config, 4>;
+
+/// The order of the BN254 (alt_bn128) G1 group.
+pub struct Bn254FrConfig;
+
+impl BigPrimeConfig<4> for Bn254FrConfig {
+    const MODULUS: [u64; 4] = [0x43e1_f593_f000_0001, 0x2833_e848_79b9_7091, 0xb850_45b6_8181_585d, 0x3064_4e72_e131_a029];
+    const GENERATOR: u64 = 5;
+}
+
+pub type Bn254Fr = BigPrimeField<Bn254FrConfig, 4>;
+
+/// `a + b + carry`, returning the low word and the carry.
+#[inline]
+const fn adc(a: u64, b: u64, carry: u64) -> (u64, u64) {
+    let t = a as u128 + b as u128 + carry as u128;
+    (t as u64, (t >> 64) as u64)
+}
+
+/// `a - b - borrow`, returning the low word and the borrow (0 or 1).
+#[inline]
+const fn sbb(a: u64, b: u64, borrow: u64) -> (u64, u64) {
+    let (d, b1) = a.overflowing_sub(b);
+    let (d, b2) = d.overflowing_sub(borrow);
+    (d, (b1 | b2) as u64)
+}
+
+/// `acc + a * b + carry`, which can't overflow 128 bits.
+#[inline]
+const fn mac(acc: u64, a: u64, b: u64, carry: u64) -> (u64, u64) {
+    let t = acc as u128 + a as u128 * b as u128 + carry as u128;
+    (t as u64, (t >> 64) as u64)
+}
+
+const fn sub_limbs<const N: usize>(a: &[u64; N], b: &[u64; N]) -> ([u64; N], u64) {
+    let mut result = [0; N];
+    let mut borrow = 0;
+    let mut i = 0;
+    while i < N {
+        (result[i], borrow) = sbb(a[i], b[i], borrow);
+        i += 1;
+    }
+    (result, borrow)
+}
+
+/// `2 * a mod p`, for `a < p`; only used to derive constants.
+const fn double_mod<const N: usize>(a: &[u64; N], p: &[u64; N]) -> [u64; N] {
+    let mut doubled = [0; N];
+    let mut carry = 0;
+    let mut i = 0;
+    while i < N {
+        doubled[i] = (a[i] << 1) | carry;
+        carry = a[i] >> 63;
+        i += 1;
+    }
+    let (reduced, borrow) = sub_limbs(&doubled, p);
+    if carry == 1 || borrow == 0 {
+        reduced
+    } else {
+        doubled
+    }
+}
+
+fn cmp_limbs<const N: usize>(a: &[u64; N], b: &[u64; N]) -> Ordering {
+    a.iter().rev().cmp(b.iter().rev())
+}
+
+/// Divides `a` in place by a single word, returning the remainder.
+fn div_rem_small<const N: usize>(a: &mut [u64; N], divisor: u64) -> u64 {
+    let mut remainder = 0u128;
+    for limb in a.iter_mut().rev() {
+        let t = (remainder << 64) | *limb as u128;
+        *limb = (t / divisor as u128) as u64;
+        remainder = t % divisor as u128;
+    }
+    remainder as u64
+}
+
+impl<C: BigPrimeConfig<N>, const N: usize> BigPrimeField<C, N> {
+    /// `-p^-1 mod 2^64`, by Newton's iteration.
+    const P_NEG_INV: u64 = {
+        assert!(C::MODULUS[0] % 2 == 1 && C::MODULUS[N - 1] >> 63 == 0, "modulus must be odd with its top bit clear");
+        let mut inverse: u64 = 1;
+        let mut i = 0;
+        while i < 6 {
+            inverse = inverse.wrapping_mul(2u64.wrapping_sub(C::MODULUS[0].wrapping_mul(inverse)));
+            i += 1;
+        }
+        inverse.wrapping_neg()
+    };
+
+    /// `2^(64N) mod p`, the Montgomery form of one.
+    const R: [u64; N] = {
+        let mut r = [0; N];
+        r[0] = 1;
+        let mut i = 0;
+        while i < 64 * N {
+            r = double_mod(&r, &C::MODULUS);
+            i += 1;
+        }
+        r
+    };
+
+    /// `2^(128N) mod p`, which takes a value into Montgomery form.
+    const R2: [u64; N] = {
+        let mut r = Self::R;
+        let mut i = 0;
+        while i < 64 * N {
+            r = double_mod(&r, &C::MODULUS);
+            i += 1;
+        }
+        r
+    };
+
+    const fn from_monty(limbs: [u64; N]) -> Self {
+        BigPrimeField { limbs, _config: PhantomData }
+    }
+
+    /// Montgomery multiplication, `a * b * 2^(-64N) mod p`. Correct for any
+    /// `a < 2^(64N)` as long as `b < p`.
+    fn monty_mul(a: &[u64; N], b: &[u64; N]) -> [u64; N] {
+        let p = &C::MODULUS;
+        let mut t = [0u64; N];
+        // the two words above t
+        let mut t_n = 0u64;
+        for &b_i in b.iter() {
+            let mut carry = 0;
+            for j in 0..N {
+                (t[j], carry) = mac(t[j], a[j], b_i, carry);
+            }
+            let (sum, t_n1) = adc(t_n, carry, 0);
+            t_n = sum;
+
+            let m = t[0].wrapping_mul(Self::P_NEG_INV);
+            let (_, mut carry) = mac(t[0], m, p[0], 0);
+            for j in 1..N {
+                (t[j - 1], carry) = mac(t[j], m, p[j], carry);
+            }
+            (t[N - 1], carry) = adc(t_n, carry, 0);
+            t_n = t_n1 + carry;
+        }
+        // t < 2p, so one conditional subtraction makes it canonical
+        let (reduced, borrow) = sub_limbs(&t, p);
+        let keep = Choice::from((t_n == 0 && borrow == 1) as u8);
+        std::array::from_fn(|i| u64::conditional_select(&reduced[i], &t[i], keep))
+    }
+
+    /// Reduces a little-endian integer of `N` limbs modulo `p`.
+    pub fn new(limbs: [u64; N]) -> Self {
+        Self::from_monty(Self::monty_mul(&limbs, &Self::R2))
+    }
+
+    /// The canonical value in `0..p`, as little-endian limbs.
+    pub fn to_limbs(&self) -> [u64; N] {
+        let mut one = [0; N];
+        one[0] = 1;
+        Self::monty_mul(&self.limbs, &one)
+    }
+
+    pub fn modulus() -> [u64; N] {
+        C::MODULUS
+    }
+
+    pub fn pow(&self, exponent: u64) -> Self {
+        FiniteField::pow(self, exponent)
+    }
+
+    /// Raises to a multi-limb exponent, given as little-endian limbs.
+    /// Not constant-time: only use with public exponents.
+    pub fn pow_limbs(&self, exponent: &[u64]) -> Self {
+        let mut result = Self::one();
+        for &limb in exponent.iter().rev() {
+            for i in (0..u64::BITS).rev() {
+                result = result.square();
+                if (limb >> i) & 1 == 1 {
+                    result = result * *self;
+                }
+            }
+        }
+        result
+    }
+
+    pub fn inverse(&self) -> Self {
+        FiniteField::inverse(self)
+    }
+
+    /// The canonical value is in the upper half of `0..p`, i.e. it reads
+    /// as negative when centered.
+    fn is_negative(&self) -> bool {
+        cmp_limbs(&(-*self).to_limbs(), &self.to_limbs()) == Ordering::Less
+    }
+}
+
+impl<C, const N: usize> Clone for BigPrimeField<C, N> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C, const N: usize> Copy for BigPrimeField<C, N> {}
+
+impl<C, const N: usize> PartialEq for BigPrimeField<C, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.limbs == other.limbs
+    }
+}
+
+impl<C, const N: usize> Eq for BigPrimeField<C, N> {}
+
+impl<C, const N: usize> Hash for BigPrimeField<C, N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.limbs.hash(state);
+    }
+}
+
+impl<C: BigPrimeConfig<N>, const N: usize> Add for BigPrimeField<C, N> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        // the top bit of p is clear, so the sum fits in N limbs
+        let mut carry = 0;
+        let sum = std::array::from_fn(|i| {
+            let (limb, next) = adc(self.limbs[i], other.limbs[i], carry);
+            carry = next;
+            limb
+        });
+        let (reduced, borrow) = sub_limbs(&sum, &C::MODULUS);
+        Self::conditional_select(&Self::from_monty(reduced), &Self::from_monty(sum), Choice::from(borrow as u8))
+    }
+}
+
+impl<C: BigPrimeConfig<N>, const N: usize> Sub for BigPrimeField<C, N> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        let (diff, borrow) = sub_limbs(&self.limbs, &other.limbs);
+        let mut wrapped = [0; N];
+        let mut carry = 0;
+        for i in 0..N {
+            (wrapped[i], carry) = adc(diff[i], C::MODULUS[i], carry);
+        }
+        Self::conditional_select(&Self::from_monty(diff), &Self::from_monty(wrapped), Choice::from(borrow as u8))
+    }
+}
+
+impl<C: BigPrimeConfig<N>, const N: usize> Neg for BigPrimeField<C, N> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::zero() - self
+    }
+}
+
+impl<C: BigPrimeConfig<N>, const N: usize> Mul for BigPrimeField<C, N> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self::from_monty(Self::monty_mul(&self.limbs, &other.limbs))
+    }
+}
+
+impl<C: BigPrimeConfig<N>, const N: usize> Div for BigPrimeField<C, N> {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, other: Self) -> Self {
+        self * other.inverse()
+    }
+}
+
+impl<C: BigPrimeConfig<N>, const N: usize> FiniteField for BigPrimeField<C, N> {
+    const BYTES: usize = 8 * N;
+
+    fn zero() -> Self {
+        Self::from_monty([0; N])
+    }
+
+    fn one() -> Self {
+        Self::from_monty(Self::R)
+    }
+
+    fn from_u64(value: u64) -> Self {
+        let mut limbs = [0; N];
+        limbs[0] = value;
+        Self::new(limbs)
+    }
+
+    fn inverse(&self) -> Self {
+        assert!(!self.is_zero(), "{} is not invertible", self);
+        // Fermat: a^(p-2) = a^-1
+        let mut two = [0; N];
+        two[0] = 2;
+        self.pow_limbs(&sub_limbs(&C::MODULUS, &two).0)
+    }
+
+    /// The canonical value, `8N` bytes little-endian.
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_limbs().iter().flat_map(|limb| limb.to_le_bytes()).collect()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, FieldError> {
+        if bytes.len() != Self::BYTES {
+            return Err(FieldError::InvalidLength { expected: Self::BYTES, found: bytes.len() });
+        }
+        let mut limbs = [0; N];
+        for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks(8)) {
+            *limb = u64::from_le_bytes(chunk.try_into().unwrap());
+        }
+        if cmp_limbs(&limbs, &C::MODULUS) != Ordering::Less {
+            return Err(FieldError::NonCanonical);
+        }
+        Ok(Self::new(limbs))
+    }
+
+    fn sample_many(byte_array: &[u8], count: usize) -> Vec<Self> {
+        hash_to_field(byte_array, DEFAULT_DST, count)
+    }
+
+    fn power_of_two_root(n: u64) -> Option<Self> {
+        Self::checked_primitive_nth_root(n)
+    }
+
+    #[cfg(feature = "rand")]
+    fn random<R: rand::RngCore + ?Sized>(rng: &mut R) -> Self {
+        let top_mask = u64::MAX >> C::MODULUS[N - 1].leading_zeros();
+        loop {
+            let mut limbs = [0; N];
+            for limb in limbs.iter_mut() {
+                *limb = rng.next_u64();
+            }
+            limbs[N - 1] &= top_mask;
+            if cmp_limbs(&limbs, &C::MODULUS) == Ordering::Less {
+                return Self::new(limbs);
+            }
+        }
+    }
+}
+
+impl<C: BigPrimeConfig<N>, const N: usize> FftField for BigPrimeField<C, N> {
+    const TWO_ADICITY: u32 = {
+        // p is odd, so p - 1 only differs from p in the lowest bit
+        let mut adicity = (C::MODULUS[0] - 1).trailing_zeros();
+        let mut i = 0;
+        while adicity == 64 * (i as u32 + 1) && i + 1 < N {
+            i += 1;
+            adicity += C::MODULUS[i].trailing_zeros();
+        }
+        adicity
+    };
+
+    fn two_adic_root_of_unity() -> Self {
+        // (p - 1) >> TWO_ADICITY
+        let mut exponent = C::MODULUS;
+        exponent[0] -= 1;
+        let (words, bits) = ((Self::TWO_ADICITY / 64) as usize, Self::TWO_ADICITY % 64);
+        let mut odd_part = [0; N];
+        for i in 0..N - words {
+            odd_part[i] = exponent[i + words] >> bits;
+            if bits > 0 && i + words + 1 < N {
+                odd_part[i] |= exponent[i + words + 1] << (64 - bits);
+            }
+        }
+        Self::generator().pow_limbs(&odd_part)
+    }
+}
+
+impl<C: BigPrimeConfig<N>, const N: usize> PrimeField for BigPrimeField<C, N> {
+    const BITS: u32 = 64 * N as u32 - C::MODULUS[N - 1].leading_zeros();
+
+    fn generator() -> Self {
+        Self::from_u64(C::GENERATOR)
+    }
+}
+
+impl<C, const N: usize> ConditionallySelectable for BigPrimeField<C, N> {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        let limbs = std::array::from_fn(|i| u64::conditional_select(&a.limbs[i], &b.limbs[i], choice));
+        BigPrimeField { limbs, _config: PhantomData }
+    }
+}
+
+impl<C, const N: usize> ConstantTimeEq for BigPrimeField<C, N> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.limbs.ct_eq(&other.limbs)
+    }
+}
+
+impl<C: BigPrimeConfig<N>, const N: usize> fmt::Debug for BigPrimeField<C, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BigPrimeField({})", self)
+    }
+}
+
+/// Decimal; the alternate form `{:#}` prints the value in `(-p/2, p/2]`.
+impl<C: BigPrimeConfig<N>, const N: usize> fmt::Display for BigPrimeField<C, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (sign, mut magnitude) = if f.alternate() && self.is_negative() {
+            ("-", (-*self).to_limbs())
+        } else {
+            ("", self.to_limbs())
+        };
+        // peel off 19 decimal digits at a time, least significant first
+        let mut chunks = Vec::new();
+        loop {
+            chunks.push(div_rem_small(&mut magnitude, 10_000_000_000_000_000_000));
+            if magnitude.iter().all(|&limb| limb == 0) {
+                break;
+            }
+        }
+        write!(f, "{}{}", sign, chunks.pop().unwrap())?;
+        for chunk in chunks.iter().rev() {
+            write!(f, "{:019}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+impl<C: BigPrimeConfig<N>, const N: usize> fmt::LowerHex for BigPrimeField<C, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let limbs = self.to_limbs();
+        let top = limbs.iter().rposition(|&limb| limb != 0).unwrap_or(0);
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{:x}", limbs[top])?;
+        for limb in limbs[..top].iter().rev() {
+            write!(f, "{:016x}", limb)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a decimal or `0x`-prefixed hexadecimal integer, optionally
+/// negated. The magnitude must be below `p`; nothing is reduced silently.
+impl<C: BigPrimeConfig<N>, const N: usize> FromStr for BigPrimeField<C, N> {
+    type Err = FieldError;
+
+    fn from_str(s: &str) -> Result<Self, FieldError> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        let (radix, digits) = match digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+            Some(rest) => (16, rest),
+            None => (10, digits),
+        };
+        if digits.is_empty() {
+            return Err(FieldError::InvalidDigit);
+        }
+        let mut limbs = [0u64; N];
+        for c in digits.chars() {
+            let digit = c.to_digit(radix).ok_or(FieldError::InvalidDigit)?;
+            // limbs = limbs * radix + digit
+            let mut carry = digit as u64;
+            for limb in limbs.iter_mut() {
+                (*limb, carry) = mac(0, *limb, radix as u64, carry);
+            }
+            if carry != 0 {
+                return Err(FieldError::NonCanonical);
+            }
+        }
+        if cmp_limbs(&limbs, &C::MODULUS) != Ordering::Less {
+            return Err(FieldError::NonCanonical);
+        }
+        let element = Self::new(limbs);
+        Ok(if negative { -element } else { element })
+    }
+}
+
+impl<C: BigPrimeConfig<N>, const N: usize> Serialize for BigPrimeField<C, N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.to_bytes())
+    }
+}
+
+impl<'de, C: BigPrimeConfig<N>, const N: usize> Deserialize<'de> for BigPrimeField<C, N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_canonical(deserializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poly::uni::Uni;
+
+    const STARK_MODULUS: &str = "3618502788666131213697322783095070105623107215331596699973092056135872020481";
+    const BN254_MODULUS: &str = "21888242871839275222246405745257275088548364400416034343698204186575808495617";
+
+    #[test]
+    fn test_parse_and_display() {
+        let minus_one: Stark252 = "-1".parse().unwrap();
+        assert_eq!(minus_one + Stark252::one(), Stark252::zero());
+        let mut expected = STARK_MODULUS.to_string();
+        expected.replace_range(expected.len() - 1.., "0");
+        assert_eq!(minus_one.to_string(), expected);
+        assert_eq!(format!("{:#}", minus_one), "-1");
+        assert_eq!(format!("{:#x}", minus_one), "0x800000000000011000000000000000000000000000000000000000000000000");
+
+        assert_eq!(STARK_MODULUS.parse::<Stark252>(), Err(FieldError::NonCanonical));
+        assert_eq!(BN254_MODULUS.parse::<Bn254Fr>(), Err(FieldError::NonCanonical));
+        assert_eq!("12a".parse::<Bn254Fr>(), Err(FieldError::InvalidDigit));
+        let x: Bn254Fr = "0x1234567890abcdef1234567890abcdef".parse().unwrap();
+        assert_eq!(x.to_limbs(), [0x1234567890abcdef, 0x1234567890abcdef, 0, 0]);
+        assert_eq!(x.to_string(), "24197857200151252728969465429440056815");
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        // values near p and 2^(64N) exercise the carries
+        let a: Bn254Fr = "0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593efffffff".parse().unwrap();
+        let b = Bn254Fr::new([u64::MAX; 4]);
+        assert_eq!(a, -Bn254Fr::from_u64(2));
+        assert_eq!(a * a, Bn254Fr::from_u64(4));
+        assert_eq!(a + a, -Bn254Fr::from_u64(4));
+        assert_eq!(a - Bn254Fr::one() - a, -Bn254Fr::one());
+        // 2^256 - 1 = 0x0e0a77c19a07df2f666ea36f7879462e36fc76959f60cd29ac96341c4ffffffa mod r
+        assert_eq!(b, "0x0e0a77c19a07df2f666ea36f7879462e36fc76959f60cd29ac96341c4ffffffa".parse().unwrap());
+        assert_eq!(b * b.inverse(), Bn254Fr::one());
+        assert_eq!((a * b) / b, a);
+
+        let c = Stark252::from_u64(u64::MAX);
+        assert_eq!(c * c, "340282366920938463426481119284349108225".parse().unwrap());
+        assert_eq!(Stark252::from_u64(7).inverse() * Stark252::from_u64(7), Stark252::one());
+    }
+
+    #[test]
+    fn test_roots_of_unity() {
+        assert_eq!(Stark252::TWO_ADICITY, 192);
+        assert_eq!(Bn254Fr::TWO_ADICITY, 28);
+        assert_eq!(Stark252::BITS, 252);
+        assert_eq!(Bn254Fr::BITS, 254);
+
+        for root in [Stark252::primitive_nth_root(1 << 20), Stark252::two_adic_root_of_unity()] {
+            assert_eq!(root.pow_limbs(&[0, 0, 0, 1]), Stark252::one());
+        }
+        let root = Bn254Fr::two_adic_root_of_unity();
+        assert_eq!(root.pow(1 << 27), -Bn254Fr::one());
+        assert_eq!(Bn254Fr::primitive_nth_root(4).square(), -Bn254Fr::one());
+    }
+
+    #[test]
+    fn test_bytes() {
+        let a = Stark252::sample(b"seed");
+        assert_eq!(a.to_bytes().len(), 32);
+        assert_eq!(Stark252::from_bytes(&a.to_bytes()), Ok(a));
+        let modulus: Vec<u8> = Stark252::modulus().iter().flat_map(|l| l.to_le_bytes()).collect();
+        assert_eq!(Stark252::from_bytes(&modulus), Err(FieldError::NonCanonical));
+        assert_eq!(Stark252::from_bytes(&[0; 31]), Err(FieldError::InvalidLength { expected: 32, found: 31 }));
+
+        let bytes = serde_pickle::to_vec(&a, Default::default()).unwrap();
+        assert_eq!(serde_pickle::from_slice::<Stark252>(&bytes, Default::default()).unwrap(), a);
+    }
+
+    #[test]
+    fn test_polynomials_over_bn254() {
+        let uni = Uni::from(vec![Bn254Fr::one(), -Bn254Fr::one()]);
+        let x = Bn254Fr::from_u64(3);
+        assert_eq!(uni.clone().evaluate(x), -Bn254Fr::from_u64(2));
+        assert_eq!(format!("{:#}", uni), "-x + 1");
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_random() {
+        let mut rng = <rand::rngs::StdRng as rand::SeedableRng>::seed_from_u64(3);
+        let a = Bn254Fr::random(&mut rng);
+        assert_ne!(a, Bn254Fr::random(&mut rng));
+        assert_eq!(a * a.inverse(), Bn254Fr::one());
+    }
+}
@@ -0,0 +1,399 @@
+//! Binary fields GF(2^8) to GF(2^128), built as Wiedemann towers:
+//! `T_0 = GF(2)` and `T_{k+1} = T_k[X_k] / (X_k^2 + X_{k-1} X_k + 1)`, with
+//! `X_{-1} = 1`. An element of `T_k` is `2^k` bits, the low half being its
+//! coordinate in `T_{k-1}` and the high half the coefficient of `X_{k-1}`.
+//! Addition is XOR, and each field is a subfield of the next by
+//! zero-extension, so packing small values wastes no space.
+
+use std::{fmt, ops::{Add, Div, Mul, Neg, Sub}, str::FromStr};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+
+use super::{
+    error::FieldError,
+    hash_to_field::{expand_message_xof, DEFAULT_DST},
+    serialization::deserialize_canonical,
+    traits::FiniteField,
+};
+
+/// Splits a level-`level` element into its two level-`level - 1` halves.
+#[inline]
+fn split(a: u128, level: u32) -> (u128, u128, u32) {
+    let half = 1 << (level - 1);
+    let mask = u128::MAX >> (128 - half);
+    (a & mask, a >> half, half)
+}
+
+/// Multiplies a level-`level` element by `X_{level-1}`:
+/// `(c0 + c1 X) X = c1 + (c0 + c1 X_{level-2}) X`.
+fn mul_by_x(c: u128, level: u32) -> u128 {
+    if level == 0 {
+        return c;
+    }
+    let (c0, c1, half) = split(c, level);
+    c1 | ((c0 ^ mul_by_x(c1, level - 1)) << half)
+}
+
+/// Karatsuba over the tower; the recursion depends only on `level`, never on
+/// the operands.
+fn mul(a: u128, b: u128, level: u32) -> u128 {
+    if level == 0 {
+        return a & b;
+    }
+    let (a0, a1, half) = split(a, level);
+    let (b0, b1, _) = split(b, level);
+    let z0 = mul(a0, b0, level - 1);
+    let z2 = mul(a1, b1, level - 1);
+    let z1 = mul(a0 ^ a1, b0 ^ b1, level - 1) ^ z0 ^ z2;
+    // X^2 = X_{level-2} X + 1
+    let low = z0 ^ z2;
+    let high = z1 ^ mul_by_x(z2, level - 1);
+    low | (high << half)
+}
+
+/// `a^-1 = conj(a) / N(a)`, where `conj(a0 + a1 X) = (a0 + a1 t) + a1 X`
+/// for `t = X_{level-2}` and the norm `N(a) = a0^2 + a0 a1 t + a1^2` lies in
+/// the subfield. Maps zero to zero.
+fn inverse(a: u128, level: u32) -> u128 {
+    if level == 0 {
+        return a;
+    }
+    let (a0, a1, half) = split(a, level);
+    let a1_t = mul_by_x(a1, level - 1);
+    let norm = mul(a0, a0 ^ a1_t, level - 1) ^ mul(a1, a1, level - 1);
+    let norm_inverse = inverse(norm, level - 1);
+    mul(a0 ^ a1_t, norm_inverse, level - 1) | (mul(a1, norm_inverse, level - 1) << half)
+}
+
+macro_rules! binary_tower_field {
+    ($(#[$doc:meta])* $name:ident, $storage:ty, $level:expr) => {
+        $(#[$doc])*
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+        #[repr(transparent)]
+        pub struct $name {
+            pub value: $storage,
+        }
+
+        impl $name {
+            /// Wraps a bit pattern; every pattern is an element.
+            pub const fn new(value: $storage) -> Self {
+                $name { value }
+            }
+
+            pub fn pow(&self, exponent: u64) -> Self {
+                FiniteField::pow(self, exponent)
+            }
+
+            pub fn inverse(&self) -> Self {
+                FiniteField::inverse(self)
+            }
+
+            pub fn checked_inverse(&self) -> Result<Self, FieldError> {
+                FiniteField::checked_inverse(self)
+            }
+
+            pub fn checked_div(&self, other: Self) -> Result<Self, FieldError> {
+                FiniteField::checked_div(self, other)
+            }
+        }
+
+        impl Add for $name {
+            type Output = Self;
+
+            #[allow(clippy::suspicious_arithmetic_impl)]
+            fn add(self, other: Self) -> Self {
+                $name { value: self.value ^ other.value }
+            }
+        }
+
+        /// Subtraction is addition in characteristic two.
+        impl Sub for $name {
+            type Output = Self;
+
+            #[allow(clippy::suspicious_arithmetic_impl)]
+            fn sub(self, other: Self) -> Self {
+                $name { value: self.value ^ other.value }
+            }
+        }
+
+        impl Neg for $name {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                self
+            }
+        }
+
+        impl Mul for $name {
+            type Output = Self;
+
+            fn mul(self, other: Self) -> Self {
+                $name { value: mul(self.value as u128, other.value as u128, $level) as $storage }
+            }
+        }
+
+        impl Div for $name {
+            type Output = Self;
+
+            #[allow(clippy::suspicious_arithmetic_impl)]
+            fn div(self, other: Self) -> Self {
+                self * other.inverse()
+            }
+        }
+
+        impl FiniteField for $name {
+            const BYTES: usize = std::mem::size_of::<$storage>();
+
+            fn zero() -> Self {
+                $name { value: 0 }
+            }
+
+            fn one() -> Self {
+                $name { value: 1 }
+            }
+
+            /// The image of the integer, i.e. its parity; use `new` for a bit
+            /// pattern.
+            fn from_u64(value: u64) -> Self {
+                $name { value: (value & 1) as $storage }
+            }
+
+            fn inverse(&self) -> Self {
+                assert!(!self.is_zero(), "{} is not invertible", self);
+                $name { value: inverse(self.value as u128, $level) as $storage }
+            }
+
+            fn to_bytes(&self) -> Vec<u8> {
+                self.value.to_le_bytes().to_vec()
+            }
+
+            /// Any bit pattern of the right length is canonical.
+            fn from_bytes(bytes: &[u8]) -> Result<Self, FieldError> {
+                let bytes = bytes.try_into().map_err(|_| FieldError::InvalidLength { expected: Self::BYTES, found: bytes.len() })?;
+                Ok($name { value: <$storage>::from_le_bytes(bytes) })
+            }
+
+            /// Uniform bits are already uniform elements, so no wide reduction
+            /// is needed.
+            fn sample_many(byte_array: &[u8], count: usize) -> Vec<Self> {
+                expand_message_xof(byte_array, DEFAULT_DST, count * Self::BYTES)
+                    .chunks(Self::BYTES)
+                    .map(|chunk| Self::from_bytes(chunk).unwrap())
+                    .collect()
+            }
+
+            #[cfg(feature = "rand")]
+            fn random<R: rand::RngCore + ?Sized>(rng: &mut R) -> Self {
+                let mut bytes = [0u8; std::mem::size_of::<$storage>()];
+                rng.fill_bytes(&mut bytes);
+                $name { value: <$storage>::from_le_bytes(bytes) }
+            }
+        }
+
+        impl ConditionallySelectable for $name {
+            fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+                $name { value: <$storage>::conditional_select(&a.value, &b.value, choice) }
+            }
+        }
+
+        impl ConstantTimeEq for $name {
+            fn ct_eq(&self, other: &Self) -> Choice {
+                self.value.ct_eq(&other.value)
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}({:#x})", stringify!($name), self.value)
+            }
+        }
+
+        /// The bit pattern as a decimal integer. There are no negative
+        /// elements, so `{:#}` prints the same.
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}", self.value)
+            }
+        }
+
+        impl fmt::LowerHex for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::LowerHex::fmt(&self.value, f)
+            }
+        }
+
+        /// Parses a decimal or `0x`-prefixed hexadecimal bit pattern. A
+        /// leading '-' is accepted and changes nothing, as `-a = a`.
+        impl FromStr for $name {
+            type Err = FieldError;
+
+            fn from_str(s: &str) -> Result<Self, FieldError> {
+                let digits = s.strip_prefix('-').unwrap_or(s);
+                let (radix, digits) = match digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+                    Some(rest) => (16, rest),
+                    None => (10, digits),
+                };
+                if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+                    return Err(FieldError::InvalidDigit);
+                }
+                let value = <$storage>::from_str_radix(digits, radix).map_err(|_| FieldError::NonCanonical)?;
+                Ok($name { value })
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_bytes(&self.to_bytes())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                deserialize_canonical(deserializer)
+            }
+        }
+    };
+}
+
+/// Each tower field contains the smaller ones as its low bits.
+macro_rules! binary_tower_embedding {
+    ($small:ident => $($large:ident),+) => {
+        $(
+            impl From<$small> for $large {
+                fn from(value: $small) -> Self {
+                    $large { value: value.value.into() }
+                }
+            }
+        )+
+    };
+}
+
+binary_tower_field!(
+    /// GF(2^8), the tower level `T_3`.
+    BinaryField8, u8, 3
+);
+binary_tower_field!(
+    /// GF(2^16), the tower level `T_4`.
+    BinaryField16, u16, 4
+);
+binary_tower_field!(
+    /// GF(2^32), the tower level `T_5`.
+    BinaryField32, u32, 5
+);
+binary_tower_field!(
+    /// GF(2^64), the tower level `T_6`.
+    BinaryField64, u64, 6
+);
+binary_tower_field!(
+    /// GF(2^128), the tower level `T_7`.
+    BinaryField128, u128, 7
+);
+
+binary_tower_embedding!(BinaryField8 => BinaryField16, BinaryField32, BinaryField64, BinaryField128);
+binary_tower_embedding!(BinaryField16 => BinaryField32, BinaryField64, BinaryField128);
+binary_tower_embedding!(BinaryField32 => BinaryField64, BinaryField128);
+binary_tower_embedding!(BinaryField64 => BinaryField128);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poly::uni::Uni;
+
+    fn samples<F: FiniteField>() -> Vec<F> {
+        F::sample_many(b"binary tower", 8)
+    }
+
+    fn check_field_axioms<F: FiniteField>() {
+        let elements = samples::<F>();
+        for &a in elements.iter() {
+            assert_eq!(a + a, F::zero());
+            assert_eq!(a * a.inverse(), F::one());
+            for &b in elements.iter() {
+                assert_eq!(a * b, b * a);
+                for &c in elements.iter() {
+                    assert_eq!((a * b) * c, a * (b * c));
+                    assert_eq!(a * (b + c), a * b + a * c);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_field_axioms() {
+        check_field_axioms::<BinaryField8>();
+        check_field_axioms::<BinaryField16>();
+        check_field_axioms::<BinaryField32>();
+        check_field_axioms::<BinaryField64>();
+        check_field_axioms::<BinaryField128>();
+    }
+
+    #[test]
+    fn test_small_tower_levels() {
+        // in T_1, X_0^2 = X_0 + 1
+        assert_eq!(BinaryField8::new(2) * BinaryField8::new(2), BinaryField8::new(3));
+        // in T_2, X_1^2 = X_0 X_1 + 1
+        assert_eq!(BinaryField8::new(4) * BinaryField8::new(4), BinaryField8::new(0b1001));
+        // every non-zero element has order dividing 2^8 - 1
+        for value in 1..=u8::MAX {
+            let a = BinaryField8::new(value);
+            assert_eq!(a.pow(255), BinaryField8::one());
+            assert_eq!(a * a.inverse(), BinaryField8::one());
+        }
+    }
+
+    #[test]
+    fn test_frobenius() {
+        for a in samples::<BinaryField32>() {
+            assert_eq!(a.pow(1 << 32), a);
+        }
+        for a in samples::<BinaryField128>() {
+            let squared = (0..128).fold(a, |x, _| x.square());
+            assert_eq!(squared, a);
+        }
+    }
+
+    #[test]
+    fn test_subfield_embedding() {
+        let small = samples::<BinaryField16>();
+        for &a in small.iter() {
+            for &b in small.iter() {
+                assert_eq!(BinaryField128::from(a * b), BinaryField128::from(a) * BinaryField128::from(b));
+                assert_eq!(BinaryField64::from(a.inverse()), BinaryField64::from(a).inverse());
+            }
+        }
+    }
+
+    #[test]
+    fn test_inverse_of_zero() {
+        assert_eq!(BinaryField64::zero().checked_inverse(), Err(FieldError::DivisionByZero));
+        assert_eq!(BinaryField8::from_u64(3), BinaryField8::one());
+        assert_eq!(BinaryField8::from_u64(4), BinaryField8::zero());
+    }
+
+    #[test]
+    fn test_bytes_and_strings() {
+        let a = BinaryField32::new(0xdead_beef);
+        assert_eq!(BinaryField32::from_bytes(&a.to_bytes()), Ok(a));
+        assert_eq!(BinaryField32::from_bytes(&[0; 3]), Err(FieldError::InvalidLength { expected: 4, found: 3 }));
+        assert_eq!("0xdeadbeef".parse(), Ok(a));
+        assert_eq!("-3735928559".parse(), Ok(a));
+        assert_eq!("0x1deadbeef".parse::<BinaryField32>(), Err(FieldError::NonCanonical));
+        assert_eq!(format!("{:x}", a), "deadbeef");
+
+        let bytes = serde_pickle::to_vec(&a, Default::default()).unwrap();
+        assert_eq!(serde_pickle::from_slice::<BinaryField32>(&bytes, Default::default()).unwrap(), a);
+    }
+
+    #[test]
+    fn test_polynomials() {
+        // (x + 2)(x + 3) = x^2 + x + 1, as 2 + 3 = 1 and 2 * 3 = X_0 (X_0 + 1) = 1
+        let f = Uni::from(vec![BinaryField8::new(2), BinaryField8::one()]);
+        let g = Uni::from(vec![BinaryField8::new(3), BinaryField8::one()]);
+        let product = f.clone() * g.clone();
+        assert_eq!(product, Uni::from(vec![BinaryField8::new(2) * BinaryField8::new(3), BinaryField8::one(), BinaryField8::one()]));
+        assert_eq!(product.clone().evaluate(BinaryField8::new(2)), BinaryField8::zero());
+        assert_eq!(product.clone() / g, f);
+        assert_eq!(format!("{}", product), "x^2 + x + 1");
+    }
+}
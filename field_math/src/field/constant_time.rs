@@ -0,0 +1,90 @@
+//! Branchless arithmetic modulo an arbitrary prime `p < 2^64`, used by
+//! `Field` for primes other than Goldilocks when the `constant-time`
+//! feature is enabled. Nothing here branches on, divides by or indexes with
+//! its operands; data-dependent choices go through `subtle` so the compiler
+//! can't turn them back into branches.
+//!
+//! Operands must be canonical (`< p`), as are the results.
+
+use subtle::{Choice, ConditionallySelectable};
+
+/// `x` reduced once, where the true value is `x + 2^64` if `carry` is set and
+/// is known to be below `2p`.
+#[inline]
+fn reduce_once(x: u64, carry: bool, p: u64) -> u64 {
+    let (reduced, borrow) = x.overflowing_sub(p);
+    // subtract p exactly when the true value is at least p
+    let subtract = Choice::from(carry as u8) | !Choice::from(borrow as u8);
+    u64::conditional_select(&x, &reduced, subtract)
+}
+
+#[inline]
+pub fn add(a: u64, b: u64, p: u64) -> u64 {
+    let (sum, carry) = a.overflowing_add(b);
+    reduce_once(sum, carry, p)
+}
+
+#[inline]
+pub fn sub(a: u64, b: u64, p: u64) -> u64 {
+    let (diff, borrow) = a.overflowing_sub(b);
+    u64::conditional_select(&diff, &diff.wrapping_add(p), Choice::from(borrow as u8))
+}
+
+#[inline]
+pub fn neg(a: u64, p: u64) -> u64 {
+    sub(0, a, p)
+}
+
+/// Reduces `x < p^2` by binary long division: the high limb is already
+/// below `p`, and each low bit is shifted in with one conditional subtraction.
+#[inline]
+pub fn reduce128(x: u128, p: u64) -> u64 {
+    let low = x as u64;
+    let mut remainder = (x >> 64) as u64;
+    for i in (0..64).rev() {
+        let (doubled, carry) = remainder.overflowing_add(remainder);
+        remainder = reduce_once(doubled | ((low >> i) & 1), carry, p);
+    }
+    remainder
+}
+
+#[inline]
+pub fn mul(a: u64, b: u64, p: u64) -> u64 {
+    reduce128(a as u128 * b as u128, p)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2^64 - 59, the largest 64-bit prime, exercises every carry
+    const PRIMES: [u64; 4] = [97, 2013265921, 2147483647, 18446744073709551557];
+
+    fn samples(p: u64) -> Vec<u64> {
+        vec![0, 1, 2, p / 2, p / 2 + 1, p - 2, p - 1, 0x1234_5678_9abc_def0 % p]
+    }
+
+    #[test]
+    fn test_matches_naive() {
+        for &p in PRIMES.iter() {
+            let m = p as u128;
+            for &a in samples(p).iter() {
+                for &b in samples(p).iter() {
+                    assert_eq!(add(a, b, p) as u128, (a as u128 + b as u128) % m, "{} + {} mod {}", a, b, p);
+                    assert_eq!(sub(a, b, p) as u128, (a as u128 + m - b as u128) % m, "{} - {} mod {}", a, b, p);
+                    assert_eq!(mul(a, b, p) as u128, (a as u128 * b as u128) % m, "{} * {} mod {}", a, b, p);
+                }
+                assert_eq!(neg(a, p) as u128, (m - a as u128) % m);
+            }
+        }
+    }
+
+    #[test]
+    fn test_reduce128_extremes() {
+        for &p in PRIMES.iter() {
+            let max = (p as u128) * (p as u128) - 1;
+            assert_eq!(reduce128(max, p) as u128, max % p as u128);
+            assert_eq!(reduce128(p as u128, p), 0);
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use std::fmt;
+
+/// Errors from field operations that have no result for some inputs.
+/// Anything fed by untrusted data (e.g. a verifier reading a proof) should
+/// use the `checked_*` operations that return these instead of panicking.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FieldError {
+    /// Zero has no multiplicative inverse.
+    DivisionByZero,
+    /// A byte encoding had the wrong length for the field.
+    InvalidLength { expected: usize, found: usize },
+    /// An integer, encoded as bytes or written out, that isn't reduced,
+    /// i.e. not below `p`.
+    NonCanonical,
+    /// A string that isn't a decimal or `0x`-prefixed hexadecimal integer.
+    InvalidDigit,
+    /// An interpolation or vanishing polynomial domain lists a point twice.
+    DuplicatePoint,
+    /// A different number of values than of domain points.
+    LengthMismatch { expected: usize, found: usize },
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldError::DivisionByZero => write!(f, "division by zero"),
+            FieldError::InvalidLength { expected, found } => {
+                write!(f, "expected a {}-byte encoding, found {} bytes", expected, found)
+            }
+            FieldError::NonCanonical => write!(f, "value is not reduced modulo p"),
+            FieldError::InvalidDigit => write!(f, "invalid digit in field element"),
+            FieldError::DuplicatePoint => write!(f, "domain contains a duplicate point"),
+            FieldError::LengthMismatch { expected, found } => {
+                write!(f, "expected {} values, one per domain point, found {}", expected, found)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FieldError {}
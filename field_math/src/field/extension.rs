@@ -0,0 +1,396 @@
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+
+use super::{error::FieldError, field_element::Goldilocks, serialization::deserialize_canonical, traits::{FftField, FiniteField}, DEFAULT_PRIME};
+
+/// A field of order `q` over which `x^D - W` is irreducible, so that
+/// `F[x] / (x^D - W)` is a field of order `q^D`. `F` is usually prime, but
+/// can itself be an extension, for towers like Mersenne31's quartic field.
+pub trait BinomiallyExtendable<const D: usize>: FftField {
+    /// The constant `W` of the irreducible polynomial `x^D - W`.
+    fn w() -> Self;
+
+    /// `W^((q - 1) / D)`, i.e. `x^(q - 1)` in the extension. The Frobenius
+    /// map `a -> a^q` multiplies the `i`-th coefficient by its `i`-th power.
+    fn dth_root() -> Self;
+
+    /// Largest `k` such that the extension has a root of unity of order
+    /// `2^k`. Defaults to the base field's two-adicity, for extensions
+    /// that only borrow its roots.
+    const EXT_TWO_ADICITY: u32 = Self::TWO_ADICITY;
+
+    /// The coefficients of a primitive `2^EXT_TWO_ADICITY`-th root of
+    /// unity of the extension.
+    fn ext_two_adic_root_of_unity() -> [Self; D] {
+        std::array::from_fn(|i| if i == 0 { Self::two_adic_root_of_unity() } else { Self::zero() })
+    }
+}
+
+/// A field `E` containing `F` as a subfield, with `E` viewed as a vector
+/// space over `F`. Every field is a degree-1 extension of itself.
+pub trait ExtensionField<F: FiniteField>: FiniteField + From<F> + Mul<F, Output = Self> {
+    const DEGREE: usize;
+
+    fn from_base(value: F) -> Self {
+        Self::from(value)
+    }
+
+    /// Builds an element from its `DEGREE` coordinates over `F`.
+    fn from_base_slice(coefficients: &[F]) -> Self;
+
+    /// The coordinates of the element over `F`.
+    fn as_base_slice(&self) -> &[F];
+}
+
+impl<F: FiniteField> ExtensionField<F> for F {
+    const DEGREE: usize = 1;
+
+    fn from_base_slice(coefficients: &[F]) -> Self {
+        assert_eq!(coefficients.len(), 1, "expected a single base field coefficient");
+        coefficients[0]
+    }
+
+    fn as_base_slice(&self) -> &[F] {
+        std::slice::from_ref(self)
+    }
+}
+
+/// The field `F[x] / (x^D - W)`; `value[i]` is the coefficient of `x^i`.
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Hash)]
+pub struct BinomialExtensionField<F, const D: usize> {
+    pub value: [F; D],
+}
+
+pub type Fp2<F> = BinomialExtensionField<F, 2>;
+pub type Fp3<F> = BinomialExtensionField<F, 3>;
+pub type Fp4<F> = BinomialExtensionField<F, 4>;
+
+impl<F: BinomiallyExtendable<D>, const D: usize> BinomialExtensionField<F, D> {
+    pub fn new(value: [F; D]) -> Self {
+        BinomialExtensionField { value }
+    }
+
+    /// The Frobenius automorphism `a -> a^q` over `F`.
+    pub fn frobenius(&self) -> Self {
+        self.repeated_frobenius(1)
+    }
+
+    /// `a -> a^(q^count)`, computed without exponentiating `a`.
+    pub fn repeated_frobenius(&self, count: usize) -> Self {
+        // x^(p^k) = x * (W^((p - 1) / D))^k
+        let z = F::dth_root().pow((count % D) as u64);
+        let mut z_i = F::one();
+        let mut value = self.value;
+        for coefficient in value.iter_mut() {
+            *coefficient = *coefficient * z_i;
+            z_i = z_i * z;
+        }
+        BinomialExtensionField { value }
+    }
+}
+
+impl<F: BinomiallyExtendable<D>, const D: usize> From<F> for BinomialExtensionField<F, D> {
+    fn from(value: F) -> Self {
+        let mut coefficients = [F::zero(); D];
+        coefficients[0] = value;
+        BinomialExtensionField { value: coefficients }
+    }
+}
+
+impl<F: BinomiallyExtendable<D>, const D: usize> Add for BinomialExtensionField<F, D> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        BinomialExtensionField { value: std::array::from_fn(|i| self.value[i] + other.value[i]) }
+    }
+}
+
+impl<F: BinomiallyExtendable<D>, const D: usize> Sub for BinomialExtensionField<F, D> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        BinomialExtensionField { value: std::array::from_fn(|i| self.value[i] - other.value[i]) }
+    }
+}
+
+impl<F: BinomiallyExtendable<D>, const D: usize> Neg for BinomialExtensionField<F, D> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        BinomialExtensionField { value: self.value.map(|c| -c) }
+    }
+}
+
+impl<F: BinomiallyExtendable<D>, const D: usize> Mul for BinomialExtensionField<F, D> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        let w = F::w();
+        let mut value = [F::zero(); D];
+        for i in 0..D {
+            for j in 0..D {
+                let term = self.value[i] * other.value[j];
+                if i + j < D {
+                    value[i + j] = value[i + j] + term;
+                } else {
+                    // x^D = W
+                    value[i + j - D] = value[i + j - D] + w * term;
+                }
+            }
+        }
+        BinomialExtensionField { value }
+    }
+}
+
+impl<F: BinomiallyExtendable<D>, const D: usize> Mul<F> for BinomialExtensionField<F, D> {
+    type Output = Self;
+
+    fn mul(self, other: F) -> Self {
+        BinomialExtensionField { value: self.value.map(|c| c * other) }
+    }
+}
+
+impl<F: BinomiallyExtendable<D>, const D: usize> Div for BinomialExtensionField<F, D> {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, other: Self) -> Self {
+        self * other.inverse()
+    }
+}
+
+impl<F: BinomiallyExtendable<D>, const D: usize> FiniteField for BinomialExtensionField<F, D> {
+    fn zero() -> Self {
+        BinomialExtensionField { value: [F::zero(); D] }
+    }
+
+    fn one() -> Self {
+        Self::from(F::one())
+    }
+
+    fn from_u64(value: u64) -> Self {
+        Self::from(F::from_u64(value))
+    }
+
+    fn inverse(&self) -> Self {
+        // a^-1 = (a^p * a^(p^2) * ... * a^(p^(D-1))) / N(a), where the norm
+        // N(a) = a * a^p * ... * a^(p^(D-1)) lies in the base field.
+        let mut conjugates = Self::one();
+        for k in 1..D {
+            conjugates = conjugates * self.repeated_frobenius(k);
+        }
+        let norm = (*self * conjugates).value[0];
+        conjugates * norm.inverse()
+    }
+
+    const BYTES: usize = D * F::BYTES;
+
+    /// The encodings of the coefficients, lowest degree first.
+    fn to_bytes(&self) -> Vec<u8> {
+        self.value.iter().flat_map(|c| c.to_bytes()).collect()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, FieldError> {
+        if bytes.len() != Self::BYTES {
+            return Err(FieldError::InvalidLength { expected: Self::BYTES, found: bytes.len() });
+        }
+        let mut value = [F::zero(); D];
+        for (coefficient, chunk) in value.iter_mut().zip(bytes.chunks(F::BYTES)) {
+            *coefficient = F::from_bytes(chunk)?;
+        }
+        Ok(BinomialExtensionField { value })
+    }
+
+    fn sample_many(byte_array: &[u8], count: usize) -> Vec<Self> {
+        F::sample_many(byte_array, count * D)
+            .chunks(D)
+            .map(|coefficients| BinomialExtensionField { value: coefficients.try_into().unwrap() })
+            .collect()
+    }
+
+    fn power_of_two_root(n: u64) -> Option<Self> {
+        Self::checked_primitive_nth_root(n)
+    }
+
+    #[cfg(feature = "rand")]
+    fn random<R: rand::RngCore + ?Sized>(rng: &mut R) -> Self {
+        BinomialExtensionField { value: std::array::from_fn(|_| F::random(rng)) }
+    }
+}
+
+impl<F: BinomiallyExtendable<D>, const D: usize> FftField for BinomialExtensionField<F, D> {
+    // Unless `F` says otherwise, only its own roots of unity are exposed.
+    const TWO_ADICITY: u32 = F::EXT_TWO_ADICITY;
+
+    fn two_adic_root_of_unity() -> Self {
+        BinomialExtensionField { value: F::ext_two_adic_root_of_unity() }
+    }
+}
+
+impl<F: BinomiallyExtendable<D>, const D: usize> ExtensionField<F> for BinomialExtensionField<F, D> {
+    const DEGREE: usize = D;
+
+    fn from_base_slice(coefficients: &[F]) -> Self {
+        BinomialExtensionField { value: coefficients.try_into().expect("wrong number of base field coefficients") }
+    }
+
+    fn as_base_slice(&self) -> &[F] {
+        &self.value
+    }
+}
+
+impl<F: BinomiallyExtendable<D>, const D: usize> ConditionallySelectable for BinomialExtensionField<F, D> {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        BinomialExtensionField { value: std::array::from_fn(|i| F::conditional_select(&a.value[i], &b.value[i], choice)) }
+    }
+}
+
+impl<F: BinomiallyExtendable<D>, const D: usize> ConstantTimeEq for BinomialExtensionField<F, D> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.value.iter().zip(other.value.iter()).fold(Choice::from(1), |acc, (a, b)| acc & a.ct_eq(b))
+    }
+}
+
+impl<F: BinomiallyExtendable<D>, const D: usize> Serialize for BinomialExtensionField<F, D> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.to_bytes())
+    }
+}
+
+impl<'de, F: BinomiallyExtendable<D>, const D: usize> Deserialize<'de> for BinomialExtensionField<F, D> {
+    fn deserialize<De: Deserializer<'de>>(deserializer: De) -> Result<Self, De::Error> {
+        deserialize_canonical(deserializer)
+    }
+}
+
+// 7 generates the multiplicative group of Goldilocks, so it is neither a
+// square nor a cube and both x^2 - 7 and x^3 - 7 are irreducible.
+impl BinomiallyExtendable<2> for Goldilocks {
+    fn w() -> Self {
+        Goldilocks::new(7)
+    }
+
+    fn dth_root() -> Self {
+        Goldilocks::new(7).pow((DEFAULT_PRIME - 1) / 2)
+    }
+}
+
+impl BinomiallyExtendable<3> for Goldilocks {
+    fn w() -> Self {
+        Goldilocks::new(7)
+    }
+
+    fn dth_root() -> Self {
+        Goldilocks::new(7).pow((DEFAULT_PRIME - 1) / 3)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_fp2() -> Fp2<Goldilocks> {
+        Fp2::new([Goldilocks::new(123456789), Goldilocks::new(DEFAULT_PRIME - 42)])
+    }
+
+    fn sample_fp3() -> Fp3<Goldilocks> {
+        Fp3::new([Goldilocks::new(3), Goldilocks::new(1 << 40), Goldilocks::new(DEFAULT_PRIME - 1)])
+    }
+
+    #[test]
+    fn test_irreducible() {
+        let w = Goldilocks::new(7);
+        assert_eq!(w.pow((DEFAULT_PRIME - 1) / 2), -Goldilocks::one());
+        assert_ne!(w.pow((DEFAULT_PRIME - 1) / 3), Goldilocks::one());
+    }
+
+    #[test]
+    fn test_fp2_mul() {
+        // x * x = W
+        let x = Fp2::new([Goldilocks::zero(), Goldilocks::one()]);
+        assert_eq!(x * x, Fp2::from(Goldilocks::new(7)));
+
+        let a = sample_fp2();
+        assert_eq!(a * Fp2::one(), a);
+        assert_eq!(a * Goldilocks::new(2), a + a);
+    }
+
+    #[test]
+    fn test_inverse() {
+        let a = sample_fp2();
+        assert_eq!(a * a.inverse(), Fp2::one());
+        let b = sample_fp3();
+        assert_eq!(b * b.inverse(), Fp3::one());
+        assert_eq!((b / b.square()) * b, Fp3::one());
+    }
+
+    #[test]
+    fn test_checked_inverse() {
+        assert_eq!(Fp2::<Goldilocks>::zero().checked_inverse(), Err(FieldError::DivisionByZero));
+        let a = sample_fp2();
+        assert_eq!(a.checked_div(a), Ok(Fp2::one()));
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_random_inverse() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..16 {
+            let a = Fp3::<Goldilocks>::random(&mut rng);
+            assert_eq!(a * a.inverse(), Fp3::one());
+        }
+    }
+
+    #[test]
+    fn test_frobenius() {
+        let a = sample_fp2();
+        assert_eq!(a.frobenius(), a.pow(DEFAULT_PRIME));
+        assert_eq!(a.repeated_frobenius(2), a);
+
+        let b = sample_fp3();
+        assert_eq!(b.frobenius(), b.pow(DEFAULT_PRIME));
+        assert_eq!(b.frobenius().frobenius().frobenius(), b);
+    }
+
+    #[test]
+    fn test_constant_time_select() {
+        let a = sample_fp3();
+        let b = Fp3::one();
+        assert_eq!(Fp3::conditional_select(&a, &b, Choice::from(1)), b);
+        assert!(bool::from(a.ct_eq(&a)));
+        // differing only in the last coefficient
+        assert!(!bool::from(a.ct_eq(&(a + Fp3::new([Goldilocks::zero(), Goldilocks::zero(), Goldilocks::one()])))));
+    }
+
+    #[test]
+    fn test_bytes_roundtrip() {
+        let b = sample_fp3();
+        assert_eq!(b.to_bytes().len(), 24);
+        assert_eq!(Fp3::from_bytes(&b.to_bytes()), Ok(b));
+        assert_eq!(Fp2::<Goldilocks>::from_bytes(&b.to_bytes()), Err(FieldError::InvalidLength { expected: 16, found: 24 }));
+        let mut bytes = b.to_bytes();
+        bytes[16..].copy_from_slice(&DEFAULT_PRIME.to_le_bytes());
+        assert_eq!(Fp3::<Goldilocks>::from_bytes(&bytes), Err(FieldError::NonCanonical));
+    }
+
+    #[test]
+    fn test_serde_roundtrip() {
+        let b = sample_fp3();
+        let bytes = serde_pickle::to_vec(&b, Default::default()).unwrap();
+        assert_eq!(serde_pickle::from_slice::<Fp3<Goldilocks>>(&bytes, Default::default()).unwrap(), b);
+        assert!(serde_pickle::from_slice::<Fp2<Goldilocks>>(&bytes, Default::default()).is_err());
+    }
+
+    #[test]
+    fn test_base_field_is_extension() {
+        let a = Goldilocks::new(5);
+        assert_eq!(<Goldilocks as ExtensionField<Goldilocks>>::DEGREE, 1);
+        assert_eq!(a.as_base_slice(), &[a]);
+        let b: Fp2<Goldilocks> = ExtensionField::from_base(a);
+        assert_eq!(ExtensionField::<Goldilocks>::as_base_slice(&b), &[a, Goldilocks::zero()]);
+    }
+}
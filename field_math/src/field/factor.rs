@@ -0,0 +1,127 @@
+//! Factorisation of 64-bit integers, used to find generators and roots of
+//! unity from the order `p - 1` of a field's multiplicative group.
+
+const SMALL_PRIMES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+fn mul_mod(a: u64, b: u64, n: u64) -> u64 {
+    ((a as u128 * b as u128) % n as u128) as u64
+}
+
+fn pow_mod(base: u64, exponent: u64, n: u64) -> u64 {
+    let mut result = 1 % n;
+    let mut base = base % n;
+    let mut exponent = exponent;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul_mod(result, base, n);
+        }
+        base = mul_mod(base, base, n);
+        exponent >>= 1;
+    }
+    result
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Deterministic Miller-Rabin; the first twelve primes as bases suffice for
+/// every 64-bit integer.
+pub fn is_prime(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    for &q in SMALL_PRIMES.iter() {
+        if n.is_multiple_of(q) {
+            return n == q;
+        }
+    }
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    'witness: for &a in SMALL_PRIMES.iter() {
+        let mut x = pow_mod(a, d, n);
+        if x == 1 || x == n - 1 {
+            continue;
+        }
+        for _ in 1..s {
+            x = mul_mod(x, x, n);
+            if x == n - 1 {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+/// A non-trivial factor of the odd composite `n`, by Pollard's rho.
+fn pollard_rho(n: u64) -> u64 {
+    for c in 1..n {
+        let f = |x: u64| ((mul_mod(x, x, n) as u128 + c as u128) % n as u128) as u64;
+        let (mut x, mut y, mut d) = (2, 2, 1);
+        while d == 1 {
+            x = f(x);
+            y = f(f(y));
+            d = gcd(x.abs_diff(y), n);
+        }
+        if d != n {
+            return d;
+        }
+    }
+    unreachable!("{} has no non-trivial factor", n)
+}
+
+/// The distinct prime factors of `n`, in increasing order.
+pub fn prime_factors(n: u64) -> Vec<u64> {
+    let mut factors = Vec::new();
+    let mut stack = vec![n];
+    while let Some(mut m) = stack.pop() {
+        for &q in SMALL_PRIMES.iter() {
+            while m % q == 0 {
+                factors.push(q);
+                m /= q;
+            }
+        }
+        if m == 1 {
+            continue;
+        }
+        if is_prime(m) {
+            factors.push(m);
+        } else {
+            let d = pollard_rho(m);
+            stack.push(d);
+            stack.push(m / d);
+        }
+    }
+    factors.sort_unstable();
+    factors.dedup();
+    factors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::DEFAULT_PRIME;
+
+    #[test]
+    fn test_is_prime() {
+        assert!(is_prime(2));
+        assert!(is_prime(DEFAULT_PRIME));
+        assert!(is_prime(2013265921));
+        assert!(!is_prime(1));
+        assert!(!is_prime(DEFAULT_PRIME - 2));
+        // strong pseudoprime to bases 2, 3, 5 and 7
+        assert!(!is_prime(3215031751));
+    }
+
+    #[test]
+    fn test_prime_factors() {
+        assert_eq!(prime_factors(DEFAULT_PRIME - 1), vec![2, 3, 5, 17, 257, 65537]);
+        assert_eq!(prime_factors(2013265920), vec![2, 3, 5]);
+        assert_eq!(prime_factors(97), vec![97]);
+        assert_eq!(prime_factors(4294967291 * 4294967279), vec![4294967279, 4294967291]);
+    }
+}
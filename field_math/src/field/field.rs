@@ -1,97 +1,259 @@
-use super::{xgcd, field_element::FieldElement};
+use super::{constant_time, error::FieldError, factor, goldilocks, field_element::FieldElement, hash_to_field::{hash_to_field, DEFAULT_DST}, powers::{self, Powers}};
 use serde::{Serialize, Deserialize};
+use subtle::{Choice, ConditionallySelectable};
 use super::DEFAULT_PRIME;
+use std::{collections::HashMap, sync::{Mutex, OnceLock, PoisonError}};
+#[cfg(feature = "rand")]
+use rand::RngCore;
 
 
-#[derive(Clone, Debug, Copy, Serialize, Deserialize)]
-pub struct Field {
-    pub p: i128,
-}
+/// The prime field of order `P`. It carries no data, so elements of the
+/// field are plain values that don't need to borrow it.
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Field<const P: u64 = DEFAULT_PRIME>;
 
 impl Field {
     pub fn new() -> Field {
-        Field { p: DEFAULT_PRIME }
+        Field
     }
+}
 
-    pub fn from(p: i128) -> Field {
-        Field { p }
+impl<const P: u64> Field<P> {
+    pub const fn p(&self) -> u64 {
+        P
     }
 
-    pub fn zero(&self) -> FieldElement {
-        FieldElement::from(0, self)
+    pub fn zero(&self) -> FieldElement<P> {
+        FieldElement::new(0)
     }
 
-    pub fn one(&self) -> FieldElement {
-        FieldElement::from(1, self)
+    pub fn one(&self) -> FieldElement<P> {
+        FieldElement::new(1)
     }
 
-    pub fn add(&self, a: FieldElement, b: FieldElement) -> FieldElement {
-        FieldElement::from((a.value + b.value) % self.p, self)
+    pub fn add(&self, a: FieldElement<P>, b: FieldElement<P>) -> FieldElement<P> {
+        if P == DEFAULT_PRIME {
+            return FieldElement::from_canonical_unchecked(goldilocks::add(a.value(), b.value()));
+        }
+        if cfg!(feature = "constant-time") {
+            return FieldElement::from_canonical_unchecked(constant_time::add(a.value(), b.value(), P));
+        }
+        FieldElement::from_canonical_unchecked(((a.value() as u128 + b.value() as u128) % P as u128) as u64)
     }
 
-    pub fn sub(&self, a: FieldElement, b: FieldElement) -> FieldElement {
-        FieldElement::from((a.value + self.p - b.value) % self.p, self)
+    pub fn sub(&self, a: FieldElement<P>, b: FieldElement<P>) -> FieldElement<P> {
+        if P == DEFAULT_PRIME {
+            return FieldElement::from_canonical_unchecked(goldilocks::sub(a.value(), b.value()));
+        }
+        if cfg!(feature = "constant-time") {
+            return FieldElement::from_canonical_unchecked(constant_time::sub(a.value(), b.value(), P));
+        }
+        FieldElement::from_canonical_unchecked(((a.value() as u128 + P as u128 - b.value() as u128) % P as u128) as u64)
     }
 
-    pub fn mul(&self, a: FieldElement, b: FieldElement) -> FieldElement {
-        FieldElement::from((a.value * b.value) % self.p, self)
+    pub fn mul(&self, a: FieldElement<P>, b: FieldElement<P>) -> FieldElement<P> {
+        if P == DEFAULT_PRIME {
+            return FieldElement::from_canonical_unchecked(goldilocks::mul(a.value(), b.value()));
+        }
+        if cfg!(feature = "constant-time") {
+            return FieldElement::from_canonical_unchecked(constant_time::mul(a.value(), b.value(), P));
+        }
+        FieldElement::from_canonical_unchecked(((a.value() as u128 * b.value() as u128) % P as u128) as u64)
     }
 
-    pub fn negate(&self, a: FieldElement) -> FieldElement {
-        FieldElement::from((self.p - a.value) % self.p, self)
+    pub fn square(&self, a: FieldElement<P>) -> FieldElement<P> {
+        if P == DEFAULT_PRIME {
+            return FieldElement::from_canonical_unchecked(goldilocks::square(a.value()));
+        }
+        self.mul(a, a)
     }
 
-    pub fn inverse(&self, a: FieldElement) -> FieldElement {
-        let (g, x, _) = xgcd(a.value, self.p);
-        if g != 1 {
-            panic!("{} is not invertible", a.value);
+    pub fn negate(&self, a: FieldElement<P>) -> FieldElement<P> {
+        if P == DEFAULT_PRIME {
+            return FieldElement::from_canonical_unchecked(goldilocks::neg(a.value()));
         }
-        FieldElement::from((x + self.p) % self.p, self)
+        if cfg!(feature = "constant-time") {
+            return FieldElement::from_canonical_unchecked(constant_time::neg(a.value(), P));
+        }
+        FieldElement::from_canonical_unchecked((P - a.value()) % P)
+    }
+
+    /// Panics if `a` is zero; see `checked_inverse`.
+    pub fn inverse(&self, a: FieldElement<P>) -> FieldElement<P> {
+        self.checked_inverse(a).unwrap_or_else(|_| panic!("{} is not invertible", a.value()))
     }
 
-    pub fn div(&self, a: FieldElement, b: FieldElement) -> FieldElement {
-        let (g, x, _x) = xgcd(b.value, self.p);
-        if g != 1 {
-            panic!("{} is not invertible", b.value);
+    pub fn checked_inverse(&self, a: FieldElement<P>) -> Result<FieldElement<P>, FieldError> {
+        if a.is_zero() {
+            return Err(FieldError::DivisionByZero);
         }
-        FieldElement::from((a.value * x) % self.p, self)
+        // Fermat: a^(p-2) = a^-1
+        Ok(self.pow(a, (P - 2) as u128))
     }
 
-    pub fn pow(&self, a: i128, b: i128) -> FieldElement {
-        FieldElement::from(a.pow(b as u32) % self.p, self)
+    /// Panics if `b` is zero; see `checked_div`.
+    pub fn div(&self, a: FieldElement<P>, b: FieldElement<P>) -> FieldElement<P> {
+        self.mul(a, self.inverse(b))
     }
 
-    pub fn pow_i32(&self, a : FieldElement, b : i32) -> FieldElement {
-        FieldElement::from(a.value.pow(b as u32) % self.p, self)
+    pub fn checked_div(&self, a: FieldElement<P>, b: FieldElement<P>) -> Result<FieldElement<P>, FieldError> {
+        Ok(self.mul(a, self.checked_inverse(b)?))
     }
 
-    pub fn primitive_nth_root(&self, n: i128) -> FieldElement {
-        if self.p == DEFAULT_PRIME {
-            assert!(n <= 1 << 64 && (n & (n - 1)) == 0, "Field does not have nth root of unity where n > 2^119 or not power of two.");
-            let mut root = FieldElement::from(DEFAULT_PRIME, self);
-            let mut order = 1 << 64;
-            while order != n {
-                root = self.pow(root.value, 2);
-                order = order / 2;
+    /// Square-and-multiply exponentiation, reducing after every step. With
+    /// the `constant-time` feature every one of the 128 exponent bits costs a
+    /// squaring and a multiplication, whatever the exponent.
+    pub fn pow(&self, a: FieldElement<P>, exponent: u128) -> FieldElement<P> {
+        if cfg!(feature = "constant-time") {
+            let mut result = self.one();
+            for i in (0..u128::BITS).rev() {
+                result = self.square(result);
+                let product = self.mul(result, a);
+                result = FieldElement::conditional_select(&result, &product, Choice::from(((exponent >> i) & 1) as u8));
+            }
+            return result;
+        }
+        let mut result = self.one();
+        let mut base = a;
+        let mut exponent = exponent;
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = self.mul(result, base);
             }
-            return root;
+            base = self.square(base);
+            exponent >>= 1;
+        }
+        result
+    }
+
+    pub fn pow_i32(&self, a : FieldElement<P>, b : i32) -> FieldElement<P> {
+        if b < 0 {
+            return self.pow(self.inverse(a), b.unsigned_abs() as u128);
+        }
+        self.pow(a, b as u128)
+    }
+
+    /// Largest `k` such that `2^k` divides `p - 1`.
+    pub const fn two_adicity(&self) -> u32 {
+        (P - 1).trailing_zeros()
+    }
+
+    /// The smallest generator of the multiplicative group, the usual choice
+    /// of offset for cosets of its subgroups.
+    pub fn generator(&self) -> FieldElement<P> {
+        if P == DEFAULT_PRIME {
+            return FieldElement::new(7);
+        }
+        // the search factors p - 1, so it runs once per modulus
+        static GENERATORS: OnceLock<Mutex<HashMap<u64, u64>>> = OnceLock::new();
+        let mut generators = GENERATORS.get_or_init(Default::default).lock().unwrap_or_else(PoisonError::into_inner);
+        let generator = *generators.entry(P).or_insert_with(|| {
+            let factors = factor::prime_factors(P - 1);
+            (2..P)
+                .map(FieldElement::<P>::new)
+                .find(|g| factors.iter().all(|q| g.pow((P - 1) / q) != self.one()))
+                .expect("multiplicative group has no generator; is P prime?")
+                .value()
+        });
+        FieldElement::from_canonical_unchecked(generator)
+    }
+
+    /// A primitive `n`-th root of unity, for any `n` dividing `p - 1`.
+    pub fn primitive_nth_root(&self, n: u64) -> FieldElement<P> {
+        assert!(n > 0 && (P - 1).is_multiple_of(n), "Field does not have nth root of unity where n does not divide p - 1.");
+        let root = self.generator().pow((P - 1) / n);
+        debug_assert!(
+            root.pow(n) == self.one() && factor::prime_factors(n).iter().all(|q| root.pow(n / q) != self.one()),
+            "{:?} is not a primitive {}-th root of unity", root, n
+        );
+        root
+    }
+
+    /// The points `offset * generator^i` for `i < size`, e.g. a FRI
+    /// evaluation domain from `self.generator()` and a root of unity.
+    pub fn coset(&self, offset: FieldElement<P>, generator: FieldElement<P>, size: usize) -> std::iter::Take<Powers<FieldElement<P>>> {
+        powers::coset(offset, generator, size)
+    }
+
+    /// The Legendre symbol `a^((p - 1) / 2)`: 0 for zero, 1 for non-zero
+    /// squares and -1 for non-squares.
+    pub fn legendre(&self, a: FieldElement<P>) -> i8 {
+        if a.is_zero() {
+            return 0;
+        }
+        if P == 2 || a.pow((P - 1) / 2) == self.one() {
+            1
         } else {
-            panic!("Unknown field, can't return root of unity.");
+            -1
+        }
+    }
+
+    pub fn is_square(&self, a: FieldElement<P>) -> bool {
+        self.legendre(a) >= 0
+    }
+
+    /// A square root of `a` by Tonelli-Shanks, or `None` if `a` is not a square.
+    /// The other root is its negation.
+    pub fn sqrt(&self, a: FieldElement<P>) -> Option<FieldElement<P>> {
+        if a.is_zero() || P == 2 {
+            return Some(a);
+        }
+        if !self.is_square(a) {
+            return None;
         }
+
+        // p - 1 = q * 2^s with q odd
+        let s = self.two_adicity();
+        let q = (P - 1) >> s;
+        // a generator is never a square, so c generates the 2-Sylow subgroup
+        let mut c = self.generator().pow(q);
+        let mut t = a.pow(q);
+        let mut root = a.pow(q.div_ceil(2));
+        let mut m = s;
+
+        // invariant: root^2 = a * t, with t of order dividing 2^(m-1)
+        while t != self.one() {
+            let mut i = 1;
+            let mut t_pow = t.square();
+            while t_pow != self.one() {
+                t_pow = t_pow.square();
+                i += 1;
+            }
+            let mut b = c;
+            for _ in 0..(m - i - 1) {
+                b = b.square();
+            }
+            m = i;
+            c = b.square();
+            t = t * c;
+            root = root * b;
+        }
+        Some(root)
     }
 
-    pub fn sample(&self, byte_array: Vec<u8>) -> FieldElement {
-        let mut acc = 0;
-        for b in byte_array {
-            acc = (acc << 8) ^ b as i128;
+    /// Hashes `byte_array` to a uniformly distributed element.
+    pub fn sample(&self, byte_array: Vec<u8>) -> FieldElement<P> {
+        hash_to_field(&byte_array, DEFAULT_DST, 1)[0]
+    }
+
+    /// A uniformly random element. Draws are masked to the bit length of `P`
+    /// and rejected until one is below `P`, so each succeeds with
+    /// probability over 1/2 and no value is favoured.
+    #[cfg(feature = "rand")]
+    pub fn random<R: RngCore + ?Sized>(&self, rng: &mut R) -> FieldElement<P> {
+        let mask = u64::MAX >> P.leading_zeros();
+        loop {
+            let candidate = rng.next_u64() & mask;
+            if candidate < P {
+                return FieldElement::new(candidate);
+            }
         }
-        FieldElement::from(acc % self.p, self)
     }
 }
 
-impl Default for Field {
-    fn default() -> Field {
-        Field::new()
+impl<const P: u64> Default for Field<P> {
+    fn default() -> Field<P> {
+        Field
     }
 }
 
@@ -103,6 +265,128 @@ mod tests {
     #[test]
     fn test_default_field() {
         let field : Field = Default::default();
-        assert_eq!(field.p, DEFAULT_PRIME);
+        assert_eq!(field.p(), DEFAULT_PRIME);
     }
-}
\ No newline at end of file
+
+    fn assert_primitive_root<const P: u64>(root: FieldElement<P>, n: u64) {
+        assert_eq!(root.pow(n), FieldElement::one());
+        for q in factor::prime_factors(n) {
+            assert_ne!(root.pow(n / q), FieldElement::one());
+        }
+    }
+
+    #[test]
+    fn test_goldilocks_roots_of_unity() {
+        let field = Field::new();
+        assert_eq!(field.two_adicity(), 32);
+        for log_n in [0, 1, 5, 17, 32] {
+            assert_primitive_root(field.primitive_nth_root(1 << log_n), 1 << log_n);
+        }
+        assert_primitive_root(field.primitive_nth_root(3 * 5 * 1024), 3 * 5 * 1024);
+    }
+
+    #[test]
+    fn test_generator() {
+        assert_eq!(Field::new().generator().value(), 7);
+        assert_eq!(Field::<97>.generator().value(), 5);
+        assert_eq!(Field::<2013265921>.generator().value(), 31);
+        assert_eq!(Field::<2147483647>.generator().value(), 7);
+    }
+
+    #[test]
+    fn test_roots_of_unity_for_other_primes() {
+        let baby_bear = Field::<2013265921>;
+        assert_eq!(baby_bear.two_adicity(), 27);
+        assert_primitive_root(baby_bear.primitive_nth_root(1 << 27), 1 << 27);
+        assert_primitive_root(Field::<97>.primitive_nth_root(12), 12);
+        // 2^31 - 1 only has a root of unity of order 2
+        assert_eq!(Field::<2147483647>.primitive_nth_root(2).value(), 2147483646);
+    }
+
+    #[test]
+    fn test_checked_inverse() {
+        let field = Field::new();
+        assert_eq!(field.checked_inverse(field.zero()), Err(FieldError::DivisionByZero));
+        assert_eq!(field.checked_div(field.one(), field.zero()), Err(FieldError::DivisionByZero));
+        let a = FieldElement::new(42);
+        assert_eq!(field.checked_div(a, a), Ok(field.one()));
+    }
+
+    #[test]
+    #[should_panic(expected = "0 is not invertible")]
+    fn test_inverse_of_zero_panics() {
+        Field::new().inverse(FieldElement::zero());
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_random() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let field = Field::<13>;
+        let mut counts = [0usize; 13];
+        for _ in 0..13000 {
+            counts[field.random(&mut rng).value() as usize] += 1;
+        }
+        // each residue is expected 1000 times
+        assert!(counts.iter().all(|&count| (850..1150).contains(&count)), "{:?}", counts);
+
+        let a = Field::new().random(&mut rng);
+        assert_ne!(a, Field::new().random(&mut rng));
+    }
+
+    #[test]
+    fn test_legendre() {
+        let field = Field::new();
+        assert_eq!(field.legendre(field.zero()), 0);
+        assert_eq!(field.legendre(FieldElement::new(4)), 1);
+        // the generator is never a square
+        assert_eq!(field.legendre(field.generator()), -1);
+        assert!(field.is_square(FieldElement::new(DEFAULT_PRIME - 1)));
+        assert!(!field.is_square(FieldElement::new(7)));
+    }
+
+    #[test]
+    fn test_goldilocks_sqrt() {
+        let field = Field::new();
+        for v in [0, 1, 2, 3, 12345678910111213, DEFAULT_PRIME - 1, 1 << 63] {
+            let a = FieldElement::new(v);
+            let root = field.sqrt(a.square()).unwrap();
+            assert!(root == a || root == -a, "sqrt({:?}^2) = {:?}", a, root);
+        }
+        // -1 is a square since p = 1 mod 4; its roots have order 4
+        let i = field.sqrt(-field.one()).unwrap();
+        assert_eq!(i.square(), -field.one());
+        assert_eq!(field.sqrt(FieldElement::new(7)), None);
+        assert_eq!(field.sqrt(FieldElement::new(7) * FieldElement::new(9)), None);
+    }
+
+    #[test]
+    fn test_sqrt_small_primes() {
+        // p = 3 mod 4, where Tonelli-Shanks takes no iterations
+        let field = Field::<103>;
+        for v in 1..103 {
+            let a = FieldElement::new(v);
+            assert_eq!(field.sqrt(a.square()).map(|r| r.square()), Some(a.square()));
+        }
+        assert_eq!(Field::<2>.sqrt(FieldElement::new(1)), Some(FieldElement::new(1)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_root_of_unity_must_divide_group_order() {
+        Field::<97>.primitive_nth_root(64);
+    }
+
+    #[test]
+    fn test_coset() {
+        let field = Field::new();
+        let omega = field.primitive_nth_root(16);
+        let domain: Vec<FieldElement> = field.coset(field.generator(), omega, 16).collect();
+        assert_eq!(domain[0], field.generator());
+        assert_eq!(domain[15] * omega, domain[0]);
+        // a coset doesn't meet the subgroup
+        assert!(omega.powers().take(16).all(|x| !domain.contains(&x)));
+    }
+}
@@ -1,94 +1,311 @@
-use super::{field::Field, xgcd};
-use std::{ops::{Add, BitXor, Div, Mul, Neg, Sub}, slice::SliceIndex};
-use serde::{Serialize, Deserialize};
+use super::{error::FieldError, field::Field, hash_to_field::{hash_to_field, DEFAULT_DST}, powers::Powers, serialization::deserialize_canonical, traits::{FftField, FiniteField, PrimeField}, DEFAULT_PRIME};
+use std::{fmt, ops::{Add, BitXor, Div, Mul, Neg, Sub}, str::FromStr};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
 
-#[derive(Clone, Debug, Copy, Serialize)]
-pub struct FieldElement<'a> {
-    pub value: i128,
-    pub field: &'a Field,
+/// An element of the prime field of order `P`, always kept in canonical
+/// form (`value < P`). Elements are `Copy` and `'static`; the field they
+/// belong to is part of the type. The layout is that of a `u64`, which lets
+/// packed kernels treat slices of elements as slices of limbs.
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct FieldElement<const P: u64 = DEFAULT_PRIME> {
+    value: u64,
 }
 
-impl<'a> FieldElement<'a> {
-    pub fn from(value: i128, field: &'a Field) -> FieldElement {
-        FieldElement { value, field }
+/// An element of the Goldilocks field `2^64 - 2^32 + 1`.
+pub type Goldilocks = FieldElement<DEFAULT_PRIME>;
+
+impl<const P: u64> FieldElement<P> {
+    pub const fn new(value: u64) -> FieldElement<P> {
+        FieldElement { value: value % P }
+    }
+
+    /// Wraps `value` without reducing it; the caller guarantees `value < P`.
+    pub(crate) const fn from_canonical_unchecked(value: u64) -> FieldElement<P> {
+        debug_assert!(value < P);
+        FieldElement { value }
+    }
+
+    /// The canonical representative, in `[0, P)`.
+    pub const fn value(&self) -> u64 {
+        self.value
+    }
+
+    /// Builds an element from a possibly negative integer, reducing it into the field.
+    pub fn from(value: i128, _field: &Field<P>) -> FieldElement<P> {
+        FieldElement { value: value.rem_euclid(P as i128) as u64 }
+    }
+
+    pub const fn zero() -> FieldElement<P> {
+        FieldElement { value: 0 }
+    }
+
+    pub const fn one() -> FieldElement<P> {
+        FieldElement { value: 1 }
+    }
+
+    pub fn field(&self) -> Field<P> {
+        Field
     }
 
     pub fn is_zero(&self) -> bool {
         self.value == 0
     }
 
-    pub fn pow(&self, exponent : u32) -> FieldElement<'a> {
-        FieldElement::from(self.value.pow(exponent) % self.field.p, self.field)
+    pub fn pow(&self, exponent : u64) -> FieldElement<P> {
+        Field.pow(*self, exponent as u128)
     }
 
+    pub fn pow_u128(&self, exponent : u128) -> FieldElement<P> {
+        Field.pow(*self, exponent)
+    }
+
+    pub fn powers(&self) -> Powers<FieldElement<P>> {
+        FiniteField::powers(self)
+    }
+
+    pub fn square(&self) -> FieldElement<P> {
+        Field.square(*self)
+    }
+
+    /// Canonical encoding: the 8-byte little-endian value.
     pub fn to_bytes(&self) -> Vec<u8> {
-        self.value.to_be_bytes().to_vec()
+        self.value.to_le_bytes().to_vec()
     }
 
     pub fn to_be_bytes(&self) -> Vec<u8> {
         self.value.to_be_bytes().to_vec()
     }
 
-    pub fn inverse(&self) -> FieldElement<'a> {
-        let (g, x, _) = xgcd(self.value, self.field.p);
-        if g != 1 {
-            panic!("{} is not invertible", self.value);
+    /// Inverse of `to_bytes`, rejecting values that aren't reduced mod `P`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<FieldElement<P>, FieldError> {
+        let bytes: [u8; 8] = bytes.try_into().map_err(|_| FieldError::InvalidLength { expected: 8, found: bytes.len() })?;
+        let value = u64::from_le_bytes(bytes);
+        if value >= P {
+            return Err(FieldError::NonCanonical);
+        }
+        Ok(FieldElement { value })
+    }
+
+    pub fn inverse(&self) -> FieldElement<P> {
+        Field.inverse(*self)
+    }
+
+    pub fn checked_inverse(&self) -> Result<FieldElement<P>, FieldError> {
+        Field.checked_inverse(*self)
+    }
+
+    pub fn checked_div(&self, other: FieldElement<P>) -> Result<FieldElement<P>, FieldError> {
+        Field.checked_div(*self, other)
+    }
+
+    pub fn legendre(&self) -> i8 {
+        Field.legendre(*self)
+    }
+
+    pub fn is_square(&self) -> bool {
+        Field.is_square(*self)
+    }
+
+    pub fn sqrt(&self) -> Option<FieldElement<P>> {
+        Field.sqrt(*self)
+    }
+
+    /// The representative in `(-p/2, p/2]`, so that e.g. `-1` reads as `-1`
+    /// rather than `p - 1`.
+    pub fn centered(&self) -> i128 {
+        if self.value > P / 2 {
+            self.value as i128 - P as i128
+        } else {
+            self.value as i128
         }
-        FieldElement::from((x + self.field.p) % self.field.p, self.field)
     }
 }
 
-impl<'a> Add for FieldElement<'a> {
-    type Output = FieldElement<'a>;
+/// Decimal; the alternate form `{:#}` prints the signed `centered` value.
+impl<const P: u64> fmt::Display for FieldElement<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "{}", self.centered())
+        } else {
+            write!(f, "{}", self.value)
+        }
+    }
+}
+
+impl<const P: u64> fmt::LowerHex for FieldElement<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.value, f)
+    }
+}
+
+/// Parses a decimal or `0x`-prefixed hexadecimal integer, optionally
+/// negated. The magnitude must be below `P`; nothing is reduced silently.
+impl<const P: u64> FromStr for FieldElement<P> {
+    type Err = FieldError;
+
+    fn from_str(s: &str) -> Result<Self, FieldError> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        let (radix, digits) = match digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+            Some(rest) => (16, rest),
+            None => (10, digits),
+        };
+        // from_str_radix would also accept a second sign
+        if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+            return Err(FieldError::InvalidDigit);
+        }
+        let value = u64::from_str_radix(digits, radix).map_err(|_| FieldError::NonCanonical)?;
+        if value >= P {
+            return Err(FieldError::NonCanonical);
+        }
+        let element = FieldElement { value };
+        Ok(if negative { -element } else { element })
+    }
+}
+
+impl<const P: u64> Add for FieldElement<P> {
+    type Output = FieldElement<P>;
+
+    fn add(self, other: FieldElement<P>) -> FieldElement<P> {
+        Field.add(self, other)
+    }
+}
+
+impl<const P: u64> Sub for FieldElement<P> {
+    type Output = FieldElement<P>;
 
-    fn add(self, other: FieldElement<'a>) -> FieldElement<'a> {
-        self.field.add(self, other)
+    fn sub(self, other: FieldElement<P>) -> FieldElement<P> {
+        Field.sub(self, other)
     }
 }
 
-impl<'a> Sub for FieldElement<'a> {
-    type Output = FieldElement<'a>;
+impl<const P: u64> Mul for FieldElement<P> {
+    type Output = FieldElement<P>;
 
-    fn sub(self, other: FieldElement<'a>) -> FieldElement<'a> {
-        self.field.sub(self, other)
+    fn mul(self, other: FieldElement<P>) -> FieldElement<P> {
+        Field.mul(self, other)
     }
 }
 
-impl<'a> Mul for FieldElement<'a> {
-    type Output = FieldElement<'a>;
+impl<const P: u64> Neg for FieldElement<P> {
+    type Output = FieldElement<P>;
+
+    fn neg(self) -> FieldElement<P> {
+        Field.negate(self)
+    }
+}
+
+impl<const P: u64> Div for FieldElement<P> {
+    type Output = FieldElement<P>;
+
+    fn div(self, other: FieldElement<P>) -> FieldElement<P> {
+        Field.div(self, other)
+    }
+}
+
+impl<const P: u64> BitXor for FieldElement<P> {
+    type Output = FieldElement<P>;
+
+    fn bitxor(self, other: FieldElement<P>) -> FieldElement<P> {
+        Field.pow(self, other.value as u128)
+    }
+}
+
+impl<const P: u64> FiniteField for FieldElement<P> {
+    fn zero() -> Self {
+        FieldElement::zero()
+    }
+
+    fn one() -> Self {
+        FieldElement::one()
+    }
+
+    fn from_u64(value: u64) -> Self {
+        FieldElement::new(value)
+    }
+
+    fn inverse(&self) -> Self {
+        Field.inverse(*self)
+    }
+
+    fn checked_inverse(&self) -> Result<Self, FieldError> {
+        Field.checked_inverse(*self)
+    }
+
+    fn square(&self) -> Self {
+        Field.square(*self)
+    }
+
+    fn pow(&self, exponent: u64) -> Self {
+        Field.pow(*self, exponent as u128)
+    }
+
+    const BYTES: usize = 8;
 
-    fn mul(self, other: FieldElement<'a>) -> FieldElement<'a> {
-        self.field.mul(self, other)
+    fn to_bytes(&self) -> Vec<u8> {
+        FieldElement::to_bytes(self)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, FieldError> {
+        FieldElement::from_bytes(bytes)
+    }
+
+    fn sample_many(byte_array: &[u8], count: usize) -> Vec<Self> {
+        hash_to_field(byte_array, DEFAULT_DST, count)
+    }
+
+    fn power_of_two_root(n: u64) -> Option<Self> {
+        Self::checked_primitive_nth_root(n)
+    }
+
+    #[cfg(feature = "rand")]
+    fn random<R: rand::RngCore + ?Sized>(rng: &mut R) -> Self {
+        Field.random(rng)
     }
 }
 
-impl<'a> Neg for FieldElement<'a> {
-    type Output = FieldElement<'a>;
+impl<const P: u64> ConditionallySelectable for FieldElement<P> {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        FieldElement { value: u64::conditional_select(&a.value, &b.value, choice) }
+    }
+}
 
-    fn neg(self) -> FieldElement<'a> {
-        self.field.negate(self)
+impl<const P: u64> ConstantTimeEq for FieldElement<P> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.value.ct_eq(&other.value)
     }
 }
 
-impl<'a> Div for FieldElement<'a> {
-    type Output = FieldElement<'a>;
+// Serialized as the canonical bytes, which is what goes into proofs.
+impl<const P: u64> Serialize for FieldElement<P> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.to_bytes())
+    }
+}
 
-    fn div(self, other: FieldElement<'a>) -> FieldElement<'a> {
-        self.field.div(self, other)
+impl<'de, const P: u64> Deserialize<'de> for FieldElement<P> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_canonical(deserializer)
     }
 }
 
-impl<'a> PartialEq for FieldElement<'a> {
-    fn eq(&self, other: &Self) -> bool {
-        self.value == other.value
+impl<const P: u64> FftField for FieldElement<P> {
+    const TWO_ADICITY: u32 = (P - 1).trailing_zeros();
+
+    fn two_adic_root_of_unity() -> Self {
+        Field.primitive_nth_root(1 << Self::TWO_ADICITY)
     }
 }
 
-impl<'a> BitXor for FieldElement<'a> {
-    type Output = FieldElement<'a>;
+impl<const P: u64> PrimeField for FieldElement<P> {
+    const BITS: u32 = 64 - P.leading_zeros();
 
-    fn bitxor(self, other: FieldElement<'a>) -> FieldElement<'a> {
-        self.field.pow(self.value, other.value)
+    fn generator() -> Self {
+        Field.generator()
     }
 }
 
@@ -127,6 +344,25 @@ mod tests {
         assert_eq!(c.value, 6);
     }
 
+    #[test]
+    fn test_mul_near_modulus() {
+        let field = Field::new();
+        let a = FieldElement::from(DEFAULT_PRIME as i128 - 1, &field);
+        let b = FieldElement::from(DEFAULT_PRIME as i128 - 2, &field);
+        // (-1) * (-2) = 2
+        assert_eq!((a * b).value, 2);
+        assert_eq!(a.square().value, 1);
+    }
+
+    #[test]
+    fn test_add_sub_near_modulus() {
+        let field = Field::new();
+        let a = FieldElement::from(DEFAULT_PRIME as i128 - 1, &field);
+        let b = FieldElement::from(5, &field);
+        assert_eq!((a + b).value, 4);
+        assert_eq!((b - a).value, 6);
+    }
+
     #[test]
     fn test_neg() {
         let field = Field::new();
@@ -144,4 +380,134 @@ mod tests {
         println!("Division result: {}", c.value);
         assert_eq!(c.value, 3);
     }
+
+    #[test]
+    fn test_pow() {
+        let field = Field::new();
+        let a = FieldElement::from(3, &field);
+        assert_eq!(a.pow(0).value, 1);
+        assert_eq!(a.pow(5).value, 243);
+        // 3^64 overflows any native integer
+        assert_eq!(a.pow(64), a.pow(32) * a.pow(32));
+        // Fermat's little theorem
+        assert_eq!(a.pow_u128(DEFAULT_PRIME as u128 - 1).value, 1);
+        assert_eq!((a ^ FieldElement::from(5, &field)).value, 243);
+    }
+
+    #[test]
+    fn test_inverse() {
+        let field = Field::new();
+        let a = FieldElement::from(DEFAULT_PRIME as i128 - 12345, &field);
+        assert_eq!((a * a.inverse()).value, 1);
+        assert_eq!(field.pow_i32(a, -3) * a.pow(3), field.one());
+    }
+
+    #[test]
+    fn test_checked_div() {
+        let a = Goldilocks::new(9);
+        assert_eq!(a.checked_div(Goldilocks::new(3)), Ok(Goldilocks::new(3)));
+        assert_eq!(a.checked_div(Goldilocks::zero()), Err(FieldError::DivisionByZero));
+        assert_eq!(Goldilocks::zero().checked_inverse(), Err(FieldError::DivisionByZero));
+    }
+
+    #[test]
+    fn test_sqrt() {
+        let a = Goldilocks::new(DEFAULT_PRIME - 5);
+        let root = a.square().sqrt().unwrap();
+        assert!(root == a || root == -a);
+        assert!(a.square().is_square());
+        assert_eq!(Goldilocks::new(7).legendre(), -1);
+        assert_eq!(Goldilocks::new(7).sqrt(), None);
+    }
+
+    #[test]
+    fn test_bytes_roundtrip() {
+        let field = Field::new();
+        let a = FieldElement::from(DEFAULT_PRIME as i128 - 7, &field);
+        assert_eq!(FieldElement::from_bytes(&a.to_bytes()), Ok(a));
+        assert_eq!(Goldilocks::new(0x0102).to_bytes(), vec![2, 1, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(Goldilocks::from_bytes(&DEFAULT_PRIME.to_le_bytes()), Err(FieldError::NonCanonical));
+        assert_eq!(Goldilocks::from_bytes(&u64::MAX.to_le_bytes()), Err(FieldError::NonCanonical));
+        assert_eq!(Goldilocks::from_bytes(&[1, 2, 3]), Err(FieldError::InvalidLength { expected: 8, found: 3 }));
+        assert_eq!(FieldElement::<97>::from_bytes(&97u64.to_le_bytes()), Err(FieldError::NonCanonical));
+    }
+
+    #[test]
+    fn test_serde_roundtrip() {
+        let a = Goldilocks::new(DEFAULT_PRIME - 3);
+        let bytes = serde_pickle::to_vec(&a, Default::default()).unwrap();
+        assert_eq!(serde_pickle::from_slice::<Goldilocks>(&bytes, Default::default()).unwrap(), a);
+
+        let elements = (a, Goldilocks::one(), vec![Goldilocks::zero(), a]);
+        let bytes = serde_pickle::to_vec(&elements, Default::default()).unwrap();
+        assert_eq!(serde_pickle::from_slice::<(Goldilocks, Goldilocks, Vec<Goldilocks>)>(&bytes, Default::default()).unwrap(), elements);
+
+        // unreduced values are rejected
+        let unreduced = Goldilocks { value: DEFAULT_PRIME };
+        let bytes = serde_pickle::to_vec(&unreduced, Default::default()).unwrap();
+        assert!(serde_pickle::from_slice::<Goldilocks>(&bytes, Default::default()).is_err());
+
+        // an endless sequence of bytes is cut off after one too many
+        let mut read = 0;
+        let endless = std::iter::repeat(0u8).inspect(|_| read += 1);
+        let deserializer = serde::de::value::SeqDeserializer::<_, serde::de::value::Error>::new(endless);
+        assert!(Goldilocks::deserialize(deserializer).is_err());
+        assert_eq!(read, Goldilocks::BYTES + 1);
+    }
+
+    #[test]
+    fn test_constant_time_select() {
+        let a = Goldilocks::new(3);
+        let b = Goldilocks::new(5);
+        assert_eq!(Goldilocks::conditional_select(&a, &b, Choice::from(0)), a);
+        assert_eq!(Goldilocks::conditional_select(&a, &b, Choice::from(1)), b);
+        assert!(bool::from(a.ct_eq(&Goldilocks::new(3))));
+        assert!(!bool::from(a.ct_eq(&b)));
+    }
+
+    #[test]
+    fn test_display() {
+        let minus_two = -Goldilocks::new(2);
+        assert_eq!(minus_two.to_string(), "18446744069414584319");
+        assert_eq!(format!("{:#}", minus_two), "-2");
+        assert_eq!(format!("{:#}", Goldilocks::new(5)), "5");
+        assert_eq!(format!("{:x}", Goldilocks::new(255)), "ff");
+        assert_eq!(format!("{:#x}", minus_two), "0xfffffffeffffffff");
+        assert_eq!(FieldElement::<97>::new(49).centered(), -48);
+        assert_eq!(FieldElement::<97>::new(48).centered(), 48);
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("42".parse::<Goldilocks>(), Ok(Goldilocks::new(42)));
+        assert_eq!("0xff".parse::<Goldilocks>(), Ok(Goldilocks::new(255)));
+        assert_eq!("-2".parse::<Goldilocks>(), Ok(-Goldilocks::new(2)));
+        assert_eq!("18446744069414584320".parse::<Goldilocks>(), Ok(-Goldilocks::one()));
+        assert_eq!("18446744069414584321".parse::<Goldilocks>(), Err(FieldError::NonCanonical));
+        assert_eq!("0xffffffffffffffffff".parse::<Goldilocks>(), Err(FieldError::NonCanonical));
+        assert_eq!("97".parse::<FieldElement<97>>(), Err(FieldError::NonCanonical));
+        assert_eq!("".parse::<Goldilocks>(), Err(FieldError::InvalidDigit));
+        assert_eq!("0x".parse::<Goldilocks>(), Err(FieldError::InvalidDigit));
+        assert_eq!("--1".parse::<Goldilocks>(), Err(FieldError::InvalidDigit));
+        assert_eq!("12a".parse::<Goldilocks>(), Err(FieldError::InvalidDigit));
+
+        let a = Goldilocks::new(DEFAULT_PRIME - 12345);
+        assert_eq!(a.to_string().parse(), Ok(a));
+        assert_eq!(format!("{:#}", a).parse(), Ok(a));
+        assert_eq!(format!("{:#x}", a).parse(), Ok(a));
+    }
+
+    #[test]
+    fn test_field_traits() {
+        fn cube<F: FiniteField>(x: F) -> F {
+            x * x * x
+        }
+        assert_eq!(cube(Goldilocks::new(3)).value, 27);
+        assert_eq!(<Goldilocks as FftField>::TWO_ADICITY, 32);
+        assert_eq!(<Goldilocks as PrimeField>::BITS, 64);
+        let root = <Goldilocks as FftField>::primitive_nth_root(1 << 20);
+        assert_eq!(root.pow(1 << 20), Goldilocks::one());
+        assert_ne!(root.pow(1 << 19), Goldilocks::one());
+        assert_eq!(Goldilocks::generator(), Goldilocks::new(7));
+    }
 }
@@ -0,0 +1,127 @@
+//! Arithmetic specialised to the Goldilocks prime `p = 2^64 - 2^32 + 1`.
+//!
+//! All functions take canonical operands (`< p`) stored in a single `u64`
+//! limb and return canonical results. Reduction uses `2^64 = 2^32 - 1` and
+//! `2^96 = -1 (mod p)`, so no division is ever performed. Corrections are
+//! applied through masks rather than branches, so timing doesn't depend on
+//! the operands.
+
+use super::DEFAULT_PRIME;
+
+const P: u64 = DEFAULT_PRIME;
+
+/// `2^64 - p = 2^32 - 1`, i.e. `2^64 mod p`.
+const EPSILON: u64 = 0xFFFF_FFFF;
+
+/// All ones if `flag` is set, zero otherwise.
+#[inline]
+fn mask(flag: bool) -> u64 {
+    0u64.wrapping_sub(flag as u64)
+}
+
+#[inline]
+fn canonicalize(x: u64) -> u64 {
+    let (reduced, borrow) = x.overflowing_sub(P);
+    // keep x if it was already below p
+    reduced ^ ((reduced ^ x) & mask(borrow))
+}
+
+#[inline]
+pub fn add(a: u64, b: u64) -> u64 {
+    let (sum, over) = a.overflowing_add(b);
+    // 2^64 wrapped away is worth EPSILON; the sum can't overflow again.
+    canonicalize(sum.wrapping_add(EPSILON & mask(over)))
+}
+
+#[inline]
+pub fn sub(a: u64, b: u64) -> u64 {
+    let (diff, borrow) = a.overflowing_sub(b);
+    // 2^64 borrowed is worth EPSILON; `diff` is at least EPSILON + 1 here.
+    diff.wrapping_sub(EPSILON & mask(borrow))
+}
+
+#[inline]
+pub fn neg(a: u64) -> u64 {
+    sub(0, a)
+}
+
+/// Reduce a 128-bit value modulo p.
+#[inline]
+pub fn reduce128(x: u128) -> u64 {
+    let x_lo = x as u64;
+    let x_hi = (x >> 64) as u64;
+    let x_hi_hi = x_hi >> 32;
+    let x_hi_lo = x_hi & EPSILON;
+
+    // x_lo - x_hi_hi * 2^96, since 2^96 = -1
+    let (t0, borrow) = x_lo.overflowing_sub(x_hi_hi);
+    let t0 = t0.wrapping_sub(EPSILON & mask(borrow));
+
+    // + x_hi_lo * 2^64, since 2^64 = EPSILON
+    let t1 = x_hi_lo * EPSILON;
+    let (res, carry) = t0.overflowing_add(t1);
+    canonicalize(res.wrapping_add(EPSILON & mask(carry)))
+}
+
+#[inline]
+pub fn mul(a: u64, b: u64) -> u64 {
+    reduce128(a as u128 * b as u128)
+}
+
+#[inline]
+pub fn square(a: u64) -> u64 {
+    mul(a, a)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn naive_mul(a: u64, b: u64) -> u64 {
+        ((a as u128 * b as u128) % P as u128) as u64
+    }
+
+    #[test]
+    fn test_add_wraps_past_2_64() {
+        assert_eq!(add(P - 1, 1), 0);
+        assert_eq!(add(P - 1, P - 1), P - 2);
+        assert_eq!(add(P - 2, 5), 3);
+    }
+
+    #[test]
+    fn test_sub_borrows() {
+        assert_eq!(sub(0, 1), P - 1);
+        assert_eq!(sub(3, P - 2), 5);
+        assert_eq!(sub(7, 7), 0);
+    }
+
+    #[test]
+    fn test_mul_matches_naive() {
+        let samples = [0, 1, 2, EPSILON, EPSILON + 1, 1 << 32, 1 << 63, P - 2, P - 1, 0x1234_5678_9abc_def0];
+        for &a in samples.iter() {
+            for &b in samples.iter() {
+                assert_eq!(mul(a, b), naive_mul(a, b), "{} * {}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_neg() {
+        assert_eq!(neg(0), 0);
+        assert_eq!(neg(1), P - 1);
+        assert_eq!(add(neg(P - 1), P - 1), 0);
+    }
+
+    #[test]
+    fn test_square() {
+        assert_eq!(square(P - 1), 1);
+        assert_eq!(square(1 << 48), naive_mul(1 << 48, 1 << 48));
+    }
+
+    #[test]
+    fn test_reduce128_extremes() {
+        assert_eq!(reduce128(u128::MAX), (u128::MAX % P as u128) as u64);
+        assert_eq!(reduce128(P as u128), 0);
+        assert_eq!(reduce128((P as u128) * (P as u128) - 1), P - 1);
+    }
+}
@@ -0,0 +1,81 @@
+//! Hashing byte strings to uniformly distributed field elements, following
+//! the `expand_message_xof` + wide reduction construction of RFC 9380.
+
+use sha3::{digest::{ExtendableOutput, Update, XofReader}, Shake256};
+
+use super::traits::PrimeField;
+
+/// Domain separation tag for challenges sampled by the library itself.
+pub const DEFAULT_DST: &[u8] = b"le_krypt-field_math-hash-to-field";
+
+/// Each element is reduced from `BITS + SECURITY_BITS` uniform bits, which
+/// puts it within `2^-SECURITY_BITS` of the uniform distribution.
+const SECURITY_BITS: u32 = 128;
+
+/// `expand_message_xof` from RFC 9380 instantiated with SHAKE256.
+pub fn expand_message_xof(msg: &[u8], dst: &[u8], len: usize) -> Vec<u8> {
+    assert!(len <= u16::MAX as usize, "cannot expand to more than 65535 bytes");
+    assert!(dst.len() <= u8::MAX as usize, "domain separation tag too long");
+
+    let mut hasher = Shake256::default();
+    hasher.update(msg);
+    hasher.update(&(len as u16).to_be_bytes());
+    hasher.update(dst);
+    hasher.update(&[dst.len() as u8]);
+
+    let mut uniform_bytes = vec![0; len];
+    hasher.finalize_xof().read(&mut uniform_bytes);
+    uniform_bytes
+}
+
+/// Hashes `msg` to `count` field elements that are indistinguishable from
+/// independent uniform samples.
+pub fn hash_to_field<F: PrimeField>(msg: &[u8], dst: &[u8], count: usize) -> Vec<F> {
+    let len_per_element = F::BITS.div_ceil(8) as usize + (SECURITY_BITS / 8) as usize;
+    let uniform_bytes = expand_message_xof(msg, dst, count * len_per_element);
+    uniform_bytes.chunks(len_per_element).map(F::from_bytes_wide).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::{field_element::{FieldElement, Goldilocks}, DEFAULT_PRIME};
+
+    #[test]
+    fn test_expand_message_xof() {
+        let a = expand_message_xof(b"abc", DEFAULT_DST, 48);
+        assert_eq!(a.len(), 48);
+        assert_eq!(a, expand_message_xof(b"abc", DEFAULT_DST, 48));
+        // the output length is part of the input, so prefixes differ
+        assert_ne!(a[..16], expand_message_xof(b"abc", DEFAULT_DST, 16)[..]);
+        assert_ne!(a, expand_message_xof(b"abc", b"other-dst", 48));
+    }
+
+    #[test]
+    fn test_from_bytes_wide() {
+        let bytes = [0xffu8; 16];
+        let expected = (u128::MAX % DEFAULT_PRIME as u128) as u64;
+        assert_eq!(Goldilocks::from_bytes_wide(&bytes), Goldilocks::new(expected));
+        assert_eq!(Goldilocks::from_bytes_wide(&[]), Goldilocks::zero());
+    }
+
+    #[test]
+    fn test_hash_to_field() {
+        let elements: Vec<Goldilocks> = hash_to_field(b"seed", DEFAULT_DST, 4);
+        assert_eq!(elements.len(), 4);
+        assert_ne!(elements[0], elements[1]);
+        assert_eq!(elements, hash_to_field(b"seed", DEFAULT_DST, 4));
+    }
+
+    #[test]
+    fn test_hash_to_small_field_is_uniform() {
+        const P: u64 = 13;
+        let mut counts = [0u32; P as usize];
+        for i in 0u32..13_000 {
+            let x: FieldElement<P> = hash_to_field(&i.to_le_bytes(), DEFAULT_DST, 1)[0];
+            counts[x.value() as usize] += 1;
+        }
+        // expected 1000 per residue, standard deviation about 30
+        assert!(counts.iter().all(|&c| (850..1150).contains(&c)), "{:?}", counts);
+    }
+}
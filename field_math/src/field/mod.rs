@@ -1,37 +1,66 @@
+pub mod big_prime;
+pub mod binary_tower;
+pub mod constant_time;
+pub mod error;
+pub mod extension;
+pub mod factor;
+#[allow(clippy::module_inception)]
 pub mod field;
 pub mod field_element;
-pub const DEFAULT_PRIME: i128 = 18446744069414584321; // Goldlilock prime 2^64 - 2^32 + 1
+pub mod goldilocks;
+pub mod hash_to_field;
+pub mod monty31;
+pub mod packed;
+pub mod powers;
+mod serialization;
+pub mod traits;
+pub const DEFAULT_PRIME: u64 = 18446744069414584321; // Goldlilock prime 2^64 - 2^32 + 1
 
-pub fn xgcd(a: i128, b: i128) -> (i128, i128, i128) {
-    xgcd_field(a, b, DEFAULT_PRIME)
-}
-
-fn xgcd_field(a: i128, b: i128, p: i128) -> (i128, i128, i128) {
-    let mut s0 = 1;
-    let mut s1 = 0;
-    let mut t0 = 0;
-    let mut t1 = 1;
-    let mut r0 = a;
-    let mut r1 = b;
+use traits::FiniteField;
 
-    while r1 != 0 {
-        let q = r0 / r1;
+/// Inverts every element with a single field inversion (Montgomery's trick).
+/// Zeros have no inverse and are mapped to zero.
+pub fn batch_inverse<F: FiniteField>(elements: &[F]) -> Vec<F> {
+    // prefix[i] is the product of the non-zero elements before i
+    let mut prefix = Vec::with_capacity(elements.len());
+    let mut acc = F::one();
+    for x in elements {
+        prefix.push(acc);
+        if !x.is_zero() {
+            acc = acc * *x;
+        }
+    }
 
-        let r_temp = r1;
-        r1 = (r0 - q * r1) % p;
-        if r1 < 0 { r1 += p; }
-        r0 = r_temp;
+    let mut acc_inverse = acc.inverse();
+    let mut inverses = vec![F::zero(); elements.len()];
+    for i in (0..elements.len()).rev() {
+        if !elements[i].is_zero() {
+            inverses[i] = acc_inverse * prefix[i];
+            acc_inverse = acc_inverse * elements[i];
+        }
+    }
+    inverses
+}
 
-        let s_temp = s1;
-        s1 = (s0 - q * s1) % p;
-        if s1 < 0 { s1 += p; }
-        s0 = s_temp;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use field_element::Goldilocks;
 
-        let t_temp = t1;
-        t1 = (t0 - q * t1) % p;
-        if t1 < 0 { t1 += p; }
-        t0 = t_temp;
+    #[test]
+    fn test_batch_inverse() {
+        let elements: Vec<Goldilocks> = (1..20).map(|i| Goldilocks::new(i * 1_000_003)).collect();
+        let inverses = batch_inverse(&elements);
+        for (x, x_inv) in elements.iter().zip(inverses.iter()) {
+            assert_eq!(*x_inv, x.inverse());
+        }
     }
 
-    (r0, s0, t0)
-}
\ No newline at end of file
+    #[test]
+    fn test_batch_inverse_with_zeros() {
+        let elements = vec![Goldilocks::zero(), Goldilocks::new(2), Goldilocks::zero(), Goldilocks::new(DEFAULT_PRIME - 1)];
+        let inverses = batch_inverse(&elements);
+        assert_eq!(inverses, vec![Goldilocks::zero(), Goldilocks::new(2).inverse(), Goldilocks::zero(), Goldilocks::new(DEFAULT_PRIME - 1)]);
+        assert!(batch_inverse::<Goldilocks>(&[]).is_empty());
+    }
+}
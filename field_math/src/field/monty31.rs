@@ -0,0 +1,466 @@
+//! Prime fields below `2^31`, kept in Montgomery form: an element `a` is
+//! stored as `a * 2^32 mod p`, so products reduce with two 32-bit
+//! multiplications instead of a division. BabyBear (`2^31 - 2^27 + 1`) has
+//! a large two-adic subgroup for FRI; Mersenne31 (`2^31 - 1`) has
+//! two-adicity 1, so its power-of-two domains live in the extension
+//! `Mersenne31Complex`, which has roots of unity of order up to `2^32`.
+//! FRI as implemented in `el_stark` takes its domain from the base field,
+//! so it can't run over Mersenne31; the NTT over `Mersenne31Complex` can.
+
+use std::{fmt, ops::{Add, Div, Mul, Neg, Sub}};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+
+use super::{
+    error::FieldError,
+    extension::{BinomialExtensionField, BinomiallyExtendable, Fp2, Fp4},
+    hash_to_field::{hash_to_field, DEFAULT_DST},
+    serialization::deserialize_canonical,
+    traits::{FftField, FiniteField, PrimeField},
+};
+
+/// An element of the prime field of order `P < 2^31`, in Montgomery form.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct Monty31<const P: u32> {
+    monty: u32,
+}
+
+/// The field of order `2^31 - 2^27 + 1 = 15 * 2^27 + 1`.
+pub type BabyBear = Monty31<0x7800_0001>;
+
+/// The field of order `2^31 - 1`.
+pub type Mersenne31 = Monty31<0x7fff_ffff>;
+
+pub type BabyBearQuartic = Fp4<BabyBear>;
+
+/// `Mersenne31[i] / (i^2 + 1)`, the "complex" Mersenne31 field.
+pub type Mersenne31Complex = Fp2<Mersenne31>;
+
+/// `Mersenne31Complex[u] / (u^2 - (2 + i))`. A binomial quartic over
+/// Mersenne31 itself doesn't exist, as `p = 3 mod 4`.
+pub type Mersenne31Quartic = Fp2<Mersenne31Complex>;
+
+/// `base^exponent mod p` on canonical integers, for use in constants.
+const fn pow_mod(base: u32, exponent: u32, p: u32) -> u32 {
+    let (mut result, mut base, mut exponent) = (1u64, base as u64, exponent);
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = result * base % p as u64;
+        }
+        base = base * base % p as u64;
+        exponent >>= 1;
+    }
+    result as u32
+}
+
+impl<const P: u32> Monty31<P> {
+    /// `-P^-1 mod 2^32`, by Newton's iteration; each step doubles the
+    /// number of correct low bits.
+    const P_NEG_INV: u32 = {
+        assert!(P % 2 == 1 && P < 1 << 31, "P must be an odd prime below 2^31");
+        let mut inverse: u32 = 1;
+        let mut i = 0;
+        while i < 5 {
+            inverse = inverse.wrapping_mul(2u32.wrapping_sub(P.wrapping_mul(inverse)));
+            i += 1;
+        }
+        inverse.wrapping_neg()
+    };
+
+    /// `2^64 mod P`, which takes a value into Montgomery form.
+    const R2: u64 = ((1u128 << 64) % P as u128) as u64;
+
+    const ONE: u32 = ((1u64 << 32) % P as u64) as u32;
+
+    /// The smallest generator of the multiplicative group, the least `g`
+    /// with `g^((P - 1) / q) != 1` for every prime `q` dividing `P - 1`.
+    /// `P - 1 < 2^31` factors by trial division, so this is found at
+    /// compile time.
+    const GENERATOR: u32 = {
+        // 2 * 3 * ... * 29 > 2^31, so there are at most nine prime factors
+        let mut factors = [0u32; 9];
+        let mut count = 0;
+        let mut n = P - 1;
+        let mut q = 2;
+        while q * q <= n {
+            if n.is_multiple_of(q) {
+                factors[count] = q;
+                count += 1;
+                while n.is_multiple_of(q) {
+                    n /= q;
+                }
+            }
+            q += 1;
+        }
+        if n > 1 {
+            factors[count] = n;
+            count += 1;
+        }
+        let mut g = 2;
+        loop {
+            let mut i = 0;
+            while i < count && pow_mod(g, (P - 1) / factors[i], P) != 1 {
+                i += 1;
+            }
+            if i == count {
+                break g;
+            }
+            g += 1;
+        }
+    };
+
+    /// Montgomery reduction: `x * 2^-32 mod P`, for `x < P * 2^32`.
+    #[inline]
+    const fn reduce(x: u64) -> u32 {
+        let m = (x as u32).wrapping_mul(Self::P_NEG_INV);
+        // x + m * P is divisible by 2^32 and below 2^64 since P < 2^31
+        let t = ((x + m as u64 * P as u64) >> 32) as u32;
+        let (reduced, borrow) = t.overflowing_sub(P);
+        // keep t if it was already below P; conditional_select isn't const, so mask by hand
+        let mask = 0u32.wrapping_sub(borrow as u32);
+        reduced ^ ((reduced ^ t) & mask)
+    }
+
+    pub const fn new(value: u32) -> Self {
+        Monty31 { monty: Self::reduce((value % P) as u64 * Self::R2) }
+    }
+
+    /// The canonical value in `0..P`.
+    pub const fn value(&self) -> u32 {
+        Self::reduce(self.monty as u64)
+    }
+
+    pub fn pow(&self, exponent: u64) -> Self {
+        FiniteField::pow(self, exponent)
+    }
+
+    pub fn inverse(&self) -> Self {
+        FiniteField::inverse(self)
+    }
+}
+
+impl<const P: u32> Add for Monty31<P> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        // both below 2^31, so the sum fits
+        let sum = self.monty + other.monty;
+        let (reduced, borrow) = sum.overflowing_sub(P);
+        Monty31 { monty: u32::conditional_select(&reduced, &sum, Choice::from(borrow as u8)) }
+    }
+}
+
+impl<const P: u32> Sub for Monty31<P> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        let (diff, borrow) = self.monty.overflowing_sub(other.monty);
+        Monty31 { monty: u32::conditional_select(&diff, &diff.wrapping_add(P), Choice::from(borrow as u8)) }
+    }
+}
+
+impl<const P: u32> Neg for Monty31<P> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::zero() - self
+    }
+}
+
+impl<const P: u32> Mul for Monty31<P> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Monty31 { monty: Self::reduce(self.monty as u64 * other.monty as u64) }
+    }
+}
+
+impl<const P: u32> Div for Monty31<P> {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, other: Self) -> Self {
+        self * other.inverse()
+    }
+}
+
+impl<const P: u32> FiniteField for Monty31<P> {
+    const BYTES: usize = 4;
+
+    fn zero() -> Self {
+        Monty31 { monty: 0 }
+    }
+
+    fn one() -> Self {
+        Monty31 { monty: Self::ONE }
+    }
+
+    fn from_u64(value: u64) -> Self {
+        Self::new((value % P as u64) as u32)
+    }
+
+    fn inverse(&self) -> Self {
+        assert!(!self.is_zero(), "{} is not invertible", self);
+        // Fermat: a^(p-2) = a^-1
+        FiniteField::pow(self, (P - 2) as u64)
+    }
+
+    /// The canonical value, 4 bytes little-endian.
+    fn to_bytes(&self) -> Vec<u8> {
+        self.value().to_le_bytes().to_vec()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, FieldError> {
+        let bytes: [u8; 4] = bytes.try_into().map_err(|_| FieldError::InvalidLength { expected: 4, found: bytes.len() })?;
+        let value = u32::from_le_bytes(bytes);
+        if value >= P {
+            return Err(FieldError::NonCanonical);
+        }
+        Ok(Self::new(value))
+    }
+
+    fn sample_many(byte_array: &[u8], count: usize) -> Vec<Self> {
+        hash_to_field(byte_array, DEFAULT_DST, count)
+    }
+
+    fn power_of_two_root(n: u64) -> Option<Self> {
+        Self::checked_primitive_nth_root(n)
+    }
+
+    #[cfg(feature = "rand")]
+    fn random<R: rand::RngCore + ?Sized>(rng: &mut R) -> Self {
+        let mask = u32::MAX >> P.leading_zeros();
+        loop {
+            let candidate = rng.next_u32() & mask;
+            if candidate < P {
+                return Self::new(candidate);
+            }
+        }
+    }
+}
+
+impl<const P: u32> FftField for Monty31<P> {
+    const TWO_ADICITY: u32 = (P - 1).trailing_zeros();
+
+    fn two_adic_root_of_unity() -> Self {
+        Self::generator().pow(((P - 1) >> Self::TWO_ADICITY) as u64)
+    }
+}
+
+impl<const P: u32> PrimeField for Monty31<P> {
+    const BITS: u32 = 32 - P.leading_zeros();
+
+    fn generator() -> Self {
+        Self::new(Self::GENERATOR)
+    }
+}
+
+impl<const P: u32> ConditionallySelectable for Monty31<P> {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Monty31 { monty: u32::conditional_select(&a.monty, &b.monty, choice) }
+    }
+}
+
+impl<const P: u32> ConstantTimeEq for Monty31<P> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.monty.ct_eq(&other.monty)
+    }
+}
+
+impl<const P: u32> fmt::Debug for Monty31<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Monty31({})", self.value())
+    }
+}
+
+/// Decimal; the alternate form `{:#}` prints the value in `(-p/2, p/2]`.
+impl<const P: u32> fmt::Display for Monty31<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = self.value();
+        if f.alternate() && value > P / 2 {
+            write!(f, "-{}", P - value)
+        } else {
+            write!(f, "{}", value)
+        }
+    }
+}
+
+impl<const P: u32> Serialize for Monty31<P> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.to_bytes())
+    }
+}
+
+impl<'de, const P: u32> Deserialize<'de> for Monty31<P> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_canonical(deserializer)
+    }
+}
+
+// 11 is not a square mod p, and as p = 1 mod 4 that makes x^4 - 11
+// irreducible.
+impl BinomiallyExtendable<4> for BabyBear {
+    fn w() -> Self {
+        BabyBear::new(11)
+    }
+
+    fn dth_root() -> Self {
+        BabyBear::new(11).pow((0x7800_0001 - 1) / 4)
+    }
+}
+
+// -1 is not a square as p = 3 mod 4.
+impl BinomiallyExtendable<2> for Mersenne31 {
+    fn w() -> Self {
+        -Mersenne31::one()
+    }
+
+    fn dth_root() -> Self {
+        -Mersenne31::one()
+    }
+
+    // p^2 - 1 = (p - 1) * 2^31 with (p - 1) / 2 odd
+    const EXT_TWO_ADICITY: u32 = 32;
+
+    // 2 + i is not a square, so its 2-part has the full order 2^32 and
+    // raising it to the odd part (p - 1) / 2 leaves exactly that
+    fn ext_two_adic_root_of_unity() -> [Self; 2] {
+        Mersenne31Complex::w().pow((0x7fff_ffff - 1) / 2).value
+    }
+}
+
+// 2 + i is not a square in Mersenne31Complex, so dth_root is -1.
+impl BinomiallyExtendable<2> for Mersenne31Complex {
+    fn w() -> Self {
+        BinomialExtensionField::new([Mersenne31::new(2), Mersenne31::one()])
+    }
+
+    fn dth_root() -> Self {
+        -Mersenne31Complex::one()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poly::{ntt::{intt, ntt}, uni::Uni};
+
+    const BABY_BEAR: u64 = 0x7800_0001;
+    const MERSENNE: u64 = 0x7fff_ffff;
+
+    fn check_arithmetic<const P: u32>() {
+        let p = P as u64;
+        let samples = [0, 1, 2, p / 2, p / 2 + 1, p - 2, p - 1, 0x1234_5678 % p];
+        for &a in samples.iter() {
+            let x = Monty31::<P>::new(a as u32);
+            assert_eq!(x.value() as u64, a);
+            assert_eq!((-x).value() as u64, (p - a) % p);
+            for &b in samples.iter() {
+                let y = Monty31::<P>::new(b as u32);
+                assert_eq!((x + y).value() as u64, (a + b) % p, "{} + {}", a, b);
+                assert_eq!((x - y).value() as u64, (a + p - b) % p, "{} - {}", a, b);
+                assert_eq!((x * y).value() as u64, (a * b) % p, "{} * {}", a, b);
+            }
+            if a != 0 {
+                assert_eq!(x * x.inverse(), Monty31::one());
+            }
+        }
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        check_arithmetic::<0x7800_0001>();
+        check_arithmetic::<0x7fff_ffff>();
+        check_arithmetic::<97>();
+    }
+
+    #[test]
+    fn test_roots_of_unity() {
+        assert_eq!(BabyBear::TWO_ADICITY, 27);
+        assert_eq!(Mersenne31::TWO_ADICITY, 1);
+        assert_eq!(BabyBear::BITS, 31);
+
+        let root = BabyBear::two_adic_root_of_unity();
+        assert_eq!(root.pow(1 << 27), BabyBear::one());
+        assert_ne!(root.pow(1 << 26), BabyBear::one());
+        let omega = BabyBear::primitive_nth_root(1 << 10);
+        assert_eq!(omega.pow(1 << 9), -BabyBear::one());
+
+        assert_eq!(BabyBear::generator(), BabyBear::new(31));
+        assert_eq!(Mersenne31::generator(), Mersenne31::new(7));
+        assert_eq!(Mersenne31::two_adic_root_of_unity(), -Mersenne31::one());
+    }
+
+    #[test]
+    fn test_bytes() {
+        let a = BabyBear::new(123456789);
+        assert_eq!(a.to_bytes(), 123456789u32.to_le_bytes().to_vec());
+        assert_eq!(BabyBear::from_bytes(&a.to_bytes()), Ok(a));
+        assert_eq!(BabyBear::from_bytes(&(BABY_BEAR as u32).to_le_bytes()), Err(FieldError::NonCanonical));
+        assert_eq!(Mersenne31::from_bytes(&[0; 8]), Err(FieldError::InvalidLength { expected: 4, found: 8 }));
+
+        let bytes = serde_pickle::to_vec(&a, Default::default()).unwrap();
+        assert_eq!(serde_pickle::from_slice::<BabyBear>(&bytes, Default::default()).unwrap(), a);
+    }
+
+    #[test]
+    fn test_baby_bear_quartic() {
+        // 11 is a non-square
+        assert_eq!(BabyBear::new(11).pow((BABY_BEAR - 1) / 2), -BabyBear::one());
+
+        let a = BabyBearQuartic::new([1, 2, 3, 4].map(BabyBear::new));
+        assert_eq!(a * a.inverse(), BabyBearQuartic::one());
+        assert_eq!(a.frobenius(), a.pow(BABY_BEAR));
+        assert_eq!(a.repeated_frobenius(4), a);
+        assert_eq!(a.to_bytes().len(), 16);
+    }
+
+    #[test]
+    fn test_mersenne31_quartic() {
+        let w = Mersenne31Complex::w();
+        let q = MERSENNE * MERSENNE;
+        assert_eq!(w.pow((q - 1) / 2), -Mersenne31Complex::one());
+
+        let i = Mersenne31Complex::new([Mersenne31::zero(), Mersenne31::one()]);
+        assert_eq!(i * i, -Mersenne31Complex::one());
+        assert_eq!(i.frobenius(), i.pow(MERSENNE));
+
+        let a = Mersenne31Quartic::new([
+            Mersenne31Complex::new([Mersenne31::new(5), Mersenne31::new(MERSENNE as u32 - 3)]),
+            Mersenne31Complex::new([Mersenne31::new(1 << 30), Mersenne31::new(17)]),
+        ]);
+        assert_eq!(a * a.inverse(), Mersenne31Quartic::one());
+        assert_eq!(a.frobenius(), a.pow(q));
+        assert_eq!(Mersenne31Quartic::from_bytes(&a.to_bytes()), Ok(a));
+        assert_eq!(Mersenne31Quartic::sample(b"seed").to_bytes().len(), 16);
+    }
+
+    #[test]
+    fn test_mersenne31_complex_roots() {
+        assert_eq!(Mersenne31::power_of_two_root(4), None);
+        assert_eq!(Mersenne31Complex::TWO_ADICITY, 32);
+        assert_eq!(Mersenne31Quartic::TWO_ADICITY, 32);
+        let root = Mersenne31Complex::two_adic_root_of_unity();
+        assert_eq!(root.pow(1 << 31), -Mersenne31Complex::one());
+        assert_eq!(Mersenne31Complex::power_of_two_root(1 << 33), None);
+
+        let coefficients = Mersenne31Complex::sample_many(b"coefficients", 64);
+        let mut values = coefficients.clone();
+        ntt(&mut values, Mersenne31Complex::primitive_nth_root(64));
+        assert_eq!(values[1], Uni::from(coefficients.clone()).evaluate(Mersenne31Complex::primitive_nth_root(64)));
+        intt(&mut values, Mersenne31Complex::primitive_nth_root(64));
+        assert_eq!(values, coefficients);
+    }
+
+    #[test]
+    fn test_polynomials_over_baby_bear() {
+        let uni = Uni::from(vec![BabyBear::new(1), BabyBear::new(2), BabyBear::new(3)]);
+        let domain: Vec<BabyBear> = (0..4).map(|i| BabyBear::primitive_nth_root(4).pow(i)).collect();
+        let values = uni.clone().evaluate_domain(domain.clone());
+        assert_eq!(values[0], BabyBear::new(6));
+        assert_eq!(values, domain.iter().map(|&x| uni.clone().evaluate(x)).collect::<Vec<_>>());
+        assert_eq!(format!("{:#}", uni * Uni::from(vec![-BabyBear::one()])), "-3x^2 - 2x - 1");
+    }
+}
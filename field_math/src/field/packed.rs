@@ -0,0 +1,367 @@
+//! Packed field elements: `W` lanes operated on together, and slice kernels
+//! built on them that the prover's hot loops use instead of going element
+//! by element.
+//!
+//! `Packed` is plain arrays with lane-wise loops, which the compiler can
+//! vectorize for any field. For Goldilocks on x86-64 built with AVX2
+//! (e.g. `RUSTFLAGS="-C target-cpu=native"`), the slice kernels switch to
+//! explicit AVX2 code instead.
+
+use std::ops::{Add, Mul, Neg, Sub};
+
+use super::traits::FiniteField;
+
+/// Number of lanes the slice kernels process at a time.
+pub const WIDTH: usize = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Packed<F, const W: usize>(pub [F; W]);
+
+pub type Packed4<F> = Packed<F, 4>;
+pub type Packed8<F> = Packed<F, 8>;
+
+impl<F: FiniteField, const W: usize> Packed<F, W> {
+    pub fn broadcast(value: F) -> Self {
+        Packed([value; W])
+    }
+
+    /// Panics unless `slice` has exactly `W` elements.
+    pub fn from_slice(slice: &[F]) -> Self {
+        Packed(slice.try_into().expect("slice length must equal the packing width"))
+    }
+
+    pub fn write_to_slice(self, out: &mut [F]) {
+        out.copy_from_slice(&self.0);
+    }
+}
+
+impl<F: FiniteField, const W: usize> Add for Packed<F, W> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Packed(std::array::from_fn(|i| self.0[i] + other.0[i]))
+    }
+}
+
+impl<F: FiniteField, const W: usize> Sub for Packed<F, W> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Packed(std::array::from_fn(|i| self.0[i] - other.0[i]))
+    }
+}
+
+impl<F: FiniteField, const W: usize> Mul for Packed<F, W> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Packed(std::array::from_fn(|i| self.0[i] * other.0[i]))
+    }
+}
+
+impl<F: FiniteField, const W: usize> Mul<F> for Packed<F, W> {
+    type Output = Self;
+
+    fn mul(self, other: F) -> Self {
+        Packed(self.0.map(|x| x * other))
+    }
+}
+
+impl<F: FiniteField, const W: usize> Neg for Packed<F, W> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Packed(self.0.map(|x| -x))
+    }
+}
+
+/// Applies `op` lane-wise to `a` and `b`, storing into `a`. The tail that
+/// doesn't fill a whole packing goes through one lane at a time.
+fn zip_with<F: FiniteField>(a: &mut [F], b: &[F], op: impl Fn(Packed<F, WIDTH>, Packed<F, WIDTH>) -> Packed<F, WIDTH>) {
+    assert_eq!(a.len(), b.len(), "slices must have the same length");
+    let mut a_chunks = a.chunks_exact_mut(WIDTH);
+    let mut b_chunks = b.chunks_exact(WIDTH);
+    for (x, y) in (&mut a_chunks).zip(&mut b_chunks) {
+        op(Packed::from_slice(x), Packed::from_slice(y)).write_to_slice(x);
+    }
+    for (x, &y) in a_chunks.into_remainder().iter_mut().zip(b_chunks.remainder()) {
+        *x = op(Packed::broadcast(*x), Packed::broadcast(y)).0[0];
+    }
+}
+
+fn map_with<F: FiniteField>(a: &mut [F], op: impl Fn(Packed<F, WIDTH>) -> Packed<F, WIDTH>) {
+    let mut chunks = a.chunks_exact_mut(WIDTH);
+    for x in &mut chunks {
+        op(Packed::from_slice(x)).write_to_slice(x);
+    }
+    for x in chunks.into_remainder() {
+        *x = op(Packed::broadcast(*x)).0[0];
+    }
+}
+
+/// `a[i] += b[i]`
+pub fn add_assign<F: FiniteField>(a: &mut [F], b: &[F]) {
+    #[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+    if let (Some(a), Some(b)) = (avx2::limbs_mut(a), avx2::limbs(b)) {
+        return avx2::add_assign(a, b);
+    }
+    zip_with(a, b, |x, y| x + y)
+}
+
+/// `a[i] -= b[i]`
+pub fn sub_assign<F: FiniteField>(a: &mut [F], b: &[F]) {
+    #[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+    if let (Some(a), Some(b)) = (avx2::limbs_mut(a), avx2::limbs(b)) {
+        return avx2::sub_assign(a, b);
+    }
+    zip_with(a, b, |x, y| x - y)
+}
+
+/// `a[i] *= b[i]`
+pub fn mul_assign<F: FiniteField>(a: &mut [F], b: &[F]) {
+    #[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+    if let (Some(a), Some(b)) = (avx2::limbs_mut(a), avx2::limbs(b)) {
+        return avx2::mul_assign(a, b);
+    }
+    zip_with(a, b, |x, y| x * y)
+}
+
+/// `a[i] += scalar`
+pub fn add_scalar_assign<F: FiniteField>(a: &mut [F], scalar: F) {
+    #[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+    if let (Some(a), Some(&[scalar])) = (avx2::limbs_mut(a), avx2::limbs(std::slice::from_ref(&scalar))) {
+        return avx2::add_scalar_assign(a, scalar);
+    }
+    let scalar = Packed::broadcast(scalar);
+    map_with(a, |x| x + scalar)
+}
+
+/// `a[i] *= scalar`
+pub fn scale_assign<F: FiniteField>(a: &mut [F], scalar: F) {
+    #[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+    if let (Some(a), Some(&[scalar])) = (avx2::limbs_mut(a), avx2::limbs(std::slice::from_ref(&scalar))) {
+        return avx2::scale_assign(a, scalar);
+    }
+    map_with(a, |x| x * scalar)
+}
+
+/// Goldilocks arithmetic on four lanes of an AVX2 register, mirroring the
+/// scalar reduction in `goldilocks`. AVX2 only compares signed 64-bit
+/// integers, so operands are offset by `2^63` before unsigned comparisons,
+/// and 64-bit products are assembled from 32-bit ones.
+#[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+mod avx2 {
+    use std::any::TypeId;
+    use std::arch::x86_64::*;
+
+    use crate::field::{field_element::Goldilocks, goldilocks, DEFAULT_PRIME};
+
+    const EPSILON: i64 = 0xFFFF_FFFF;
+
+    /// The limbs of `slice` if it holds Goldilocks elements.
+    pub fn limbs<F: 'static>(slice: &[F]) -> Option<&[u64]> {
+        if TypeId::of::<F>() != TypeId::of::<Goldilocks>() {
+            return None;
+        }
+        // SAFETY: F is Goldilocks, which is repr(transparent) over u64
+        Some(unsafe { std::slice::from_raw_parts(slice.as_ptr() as *const u64, slice.len()) })
+    }
+
+    pub fn limbs_mut<F: 'static>(slice: &mut [F]) -> Option<&mut [u64]> {
+        if TypeId::of::<F>() != TypeId::of::<Goldilocks>() {
+            return None;
+        }
+        // SAFETY: as in `limbs`; every u64 written back is a canonical element
+        Some(unsafe { std::slice::from_raw_parts_mut(slice.as_mut_ptr() as *mut u64, slice.len()) })
+    }
+
+    #[inline]
+    unsafe fn shift(x: __m256i) -> __m256i {
+        _mm256_xor_si256(x, _mm256_set1_epi64x(i64::MIN))
+    }
+
+    /// All ones in the lanes where `x < y` as unsigned integers.
+    #[inline]
+    unsafe fn less_than(x: __m256i, y: __m256i) -> __m256i {
+        _mm256_cmpgt_epi64(shift(y), shift(x))
+    }
+
+    #[inline]
+    unsafe fn canonicalize(x: __m256i) -> __m256i {
+        let p = _mm256_set1_epi64x(DEFAULT_PRIME as i64);
+        _mm256_blendv_epi8(_mm256_sub_epi64(x, p), x, less_than(x, p))
+    }
+
+    #[inline]
+    unsafe fn add(a: __m256i, b: __m256i) -> __m256i {
+        let sum = _mm256_add_epi64(a, b);
+        let carry = less_than(sum, a);
+        canonicalize(_mm256_add_epi64(sum, _mm256_and_si256(carry, _mm256_set1_epi64x(EPSILON))))
+    }
+
+    #[inline]
+    unsafe fn sub(a: __m256i, b: __m256i) -> __m256i {
+        let diff = _mm256_sub_epi64(a, b);
+        let borrow = less_than(a, b);
+        _mm256_sub_epi64(diff, _mm256_and_si256(borrow, _mm256_set1_epi64x(EPSILON)))
+    }
+
+    #[inline]
+    unsafe fn mul(a: __m256i, b: __m256i) -> __m256i {
+        let epsilon = _mm256_set1_epi64x(EPSILON);
+        let a_hi = _mm256_srli_epi64(a, 32);
+        let b_hi = _mm256_srli_epi64(b, 32);
+        // mul_epu32 multiplies the low 32 bits of each lane
+        let ll = _mm256_mul_epu32(a, b);
+        let lh = _mm256_mul_epu32(a, b_hi);
+        let hl = _mm256_mul_epu32(a_hi, b);
+        let hh = _mm256_mul_epu32(a_hi, b_hi);
+        // none of these sums can overflow 64 bits
+        let t = _mm256_add_epi64(hl, _mm256_srli_epi64(ll, 32));
+        let u = _mm256_add_epi64(lh, _mm256_and_si256(t, epsilon));
+        let lo = _mm256_or_si256(_mm256_slli_epi64(u, 32), _mm256_and_si256(ll, epsilon));
+        let hi = _mm256_add_epi64(_mm256_add_epi64(hh, _mm256_srli_epi64(t, 32)), _mm256_srli_epi64(u, 32));
+        reduce128(hi, lo)
+    }
+
+    /// `goldilocks::reduce128` on `hi * 2^64 + lo`.
+    #[inline]
+    unsafe fn reduce128(hi: __m256i, lo: __m256i) -> __m256i {
+        let epsilon = _mm256_set1_epi64x(EPSILON);
+        let hi_hi = _mm256_srli_epi64(hi, 32);
+        let hi_lo = _mm256_and_si256(hi, epsilon);
+
+        let borrow = less_than(lo, hi_hi);
+        let t0 = _mm256_sub_epi64(_mm256_sub_epi64(lo, hi_hi), _mm256_and_si256(borrow, epsilon));
+
+        let t1 = _mm256_sub_epi64(_mm256_slli_epi64(hi_lo, 32), hi_lo);
+        let res = _mm256_add_epi64(t0, t1);
+        let carry = less_than(res, t0);
+        canonicalize(_mm256_add_epi64(res, _mm256_and_si256(carry, epsilon)))
+    }
+
+    fn zip_with(a: &mut [u64], b: &[u64], op: unsafe fn(__m256i, __m256i) -> __m256i, scalar: fn(u64, u64) -> u64) {
+        assert_eq!(a.len(), b.len(), "slices must have the same length");
+        let mut a_chunks = a.chunks_exact_mut(4);
+        let mut b_chunks = b.chunks_exact(4);
+        for (x, y) in (&mut a_chunks).zip(&mut b_chunks) {
+            // SAFETY: both chunks hold four u64s; loads and stores are unaligned
+            unsafe {
+                let result = op(_mm256_loadu_si256(x.as_ptr() as *const __m256i), _mm256_loadu_si256(y.as_ptr() as *const __m256i));
+                _mm256_storeu_si256(x.as_mut_ptr() as *mut __m256i, result);
+            }
+        }
+        for (x, &y) in a_chunks.into_remainder().iter_mut().zip(b_chunks.remainder()) {
+            *x = scalar(*x, y);
+        }
+    }
+
+    fn map_with(a: &mut [u64], scalar_operand: u64, op: unsafe fn(__m256i, __m256i) -> __m256i, scalar: fn(u64, u64) -> u64) {
+        let mut chunks = a.chunks_exact_mut(4);
+        // SAFETY: AVX2 is enabled at compile time
+        let operand = unsafe { _mm256_set1_epi64x(scalar_operand as i64) };
+        for x in &mut chunks {
+            // SAFETY: as in `zip_with`
+            unsafe {
+                let result = op(_mm256_loadu_si256(x.as_ptr() as *const __m256i), operand);
+                _mm256_storeu_si256(x.as_mut_ptr() as *mut __m256i, result);
+            }
+        }
+        for x in chunks.into_remainder() {
+            *x = scalar(*x, scalar_operand);
+        }
+    }
+
+    pub fn add_assign(a: &mut [u64], b: &[u64]) {
+        zip_with(a, b, add, goldilocks::add)
+    }
+
+    pub fn sub_assign(a: &mut [u64], b: &[u64]) {
+        zip_with(a, b, sub, goldilocks::sub)
+    }
+
+    pub fn mul_assign(a: &mut [u64], b: &[u64]) {
+        zip_with(a, b, mul, goldilocks::mul)
+    }
+
+    pub fn add_scalar_assign(a: &mut [u64], scalar: u64) {
+        map_with(a, scalar, add, goldilocks::add)
+    }
+
+    pub fn scale_assign(a: &mut [u64], scalar: u64) {
+        map_with(a, scalar, mul, goldilocks::mul)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::{field_element::{FieldElement, Goldilocks}, DEFAULT_PRIME};
+
+    // covers every carry and borrow case, and a length that leaves a tail
+    fn operands<F: FiniteField>() -> (Vec<F>, Vec<F>) {
+        let special = [0, 1, 2, 0xFFFF_FFFF, 1 << 32, 1 << 63, DEFAULT_PRIME - 2, DEFAULT_PRIME - 1];
+        let mut a = Vec::new();
+        let mut b = Vec::new();
+        for &x in special.iter() {
+            for &y in special.iter() {
+                a.push(F::from_u64(x));
+                b.push(F::from_u64(y));
+            }
+        }
+        a.extend((0..11).map(|i| F::from_u64(0x9E37_79B9_7F4A_7C15u64.wrapping_mul(i + 1))));
+        b.extend((0..11).map(|i| F::from_u64(0xC2B2_AE3D_27D4_EB4Fu64.wrapping_mul(i + 7))));
+        (a, b)
+    }
+
+    fn check_kernels<F: FiniteField>() {
+        let (a, b) = operands::<F>();
+        let expect = |op: fn(F, F) -> F| -> Vec<F> { a.iter().zip(b.iter()).map(|(&x, &y)| op(x, y)).collect() };
+
+        let mut sum = a.clone();
+        add_assign(&mut sum, &b);
+        assert_eq!(sum, expect(|x, y| x + y));
+
+        let mut difference = a.clone();
+        sub_assign(&mut difference, &b);
+        assert_eq!(difference, expect(|x, y| x - y));
+
+        let mut product = a.clone();
+        mul_assign(&mut product, &b);
+        assert_eq!(product, expect(|x, y| x * y));
+
+        let scalar = b[13];
+        let mut scaled = a.clone();
+        scale_assign(&mut scaled, scalar);
+        assert_eq!(scaled, a.iter().map(|&x| x * scalar).collect::<Vec<F>>());
+
+        let mut shifted = a.clone();
+        add_scalar_assign(&mut shifted, scalar);
+        assert_eq!(shifted, a.iter().map(|&x| x + scalar).collect::<Vec<F>>());
+    }
+
+    #[test]
+    fn test_kernels_goldilocks() {
+        check_kernels::<Goldilocks>();
+    }
+
+    #[test]
+    fn test_kernels_other_prime() {
+        check_kernels::<FieldElement<2013265921>>();
+    }
+
+    #[test]
+    fn test_packed_ops() {
+        let a: Packed4<Goldilocks> = Packed([1, 2, 3, 4].map(Goldilocks::new));
+        let b = Packed4::broadcast(Goldilocks::new(5));
+        assert_eq!(a + b, Packed([6, 7, 8, 9].map(Goldilocks::new)));
+        assert_eq!(a * b, a * Goldilocks::new(5));
+        assert_eq!(a - a, Packed4::broadcast(Goldilocks::zero()));
+        assert_eq!(-a + a, Packed4::broadcast(Goldilocks::zero()));
+
+        let mut out = [Goldilocks::zero(); 4];
+        (a * a).write_to_slice(&mut out);
+        assert_eq!(out, [1, 4, 9, 16].map(Goldilocks::new));
+        assert_eq!(Packed8::from_slice(&[Goldilocks::one(); 8]), Packed8::broadcast(Goldilocks::one()));
+    }
+}
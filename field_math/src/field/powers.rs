@@ -0,0 +1,62 @@
+//! Successive powers by repeated multiplication, for building evaluation
+//! domains without an exponentiation per point.
+
+use super::traits::FiniteField;
+
+/// The infinite sequence `start, start * base, start * base^2, ...`.
+#[derive(Clone, Debug)]
+pub struct Powers<F> {
+    current: F,
+    base: F,
+}
+
+impl<F: FiniteField> Powers<F> {
+    pub fn new(start: F, base: F) -> Self {
+        Powers { current: start, base }
+    }
+}
+
+impl<F: FiniteField> Iterator for Powers<F> {
+    type Item = F;
+
+    fn next(&mut self) -> Option<F> {
+        let current = self.current;
+        self.current = current * self.base;
+        Some(current)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+}
+
+/// The first `size` points `offset * generator^i` of a coset of the
+/// subgroup generated by `generator`.
+pub fn coset<F: FiniteField>(offset: F, generator: F, size: usize) -> std::iter::Take<Powers<F>> {
+    Powers::new(offset, generator).take(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::{field_element::Goldilocks, monty31::BabyBear, traits::FftField};
+
+    #[test]
+    fn test_powers() {
+        let g = Goldilocks::new(3);
+        let powers: Vec<Goldilocks> = g.powers().take(5).collect();
+        assert_eq!(powers, (0..5).map(|i| g.pow(i)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_coset() {
+        let omega = BabyBear::primitive_nth_root(8);
+        let offset = BabyBear::new(31);
+        let domain: Vec<BabyBear> = coset(offset, omega, 8).collect();
+        assert_eq!(domain.len(), 8);
+        assert_eq!(domain[5], offset * omega.pow(5));
+        // the coset is closed under multiplication by omega
+        assert_eq!(domain[7] * omega, domain[0]);
+        assert_eq!(coset(offset, omega, 0).count(), 0);
+    }
+}
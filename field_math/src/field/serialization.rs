@@ -0,0 +1,39 @@
+//! Serde support shared by every field: elements go over the wire as their
+//! canonical `to_bytes` encoding, and decoding rejects anything else.
+
+use std::{fmt, marker::PhantomData};
+
+use serde::{de::{self, SeqAccess, Visitor}, Deserializer};
+
+use super::traits::FiniteField;
+
+struct CanonicalBytesVisitor<F>(PhantomData<F>);
+
+impl<'de, F: FiniteField> Visitor<'de> for CanonicalBytesVisitor<F> {
+    type Value = F;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "a canonical {}-byte field element encoding", F::BYTES)
+    }
+
+    fn visit_bytes<Err: de::Error>(self, bytes: &[u8]) -> Result<F, Err> {
+        F::from_bytes(bytes).map_err(Err::custom)
+    }
+
+    // formats without a byte string type hand us a sequence of integers;
+    // stop reading as soon as it is too long for an element
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<F, A::Error> {
+        let mut bytes = Vec::with_capacity(F::BYTES);
+        while let Some(byte) = seq.next_element::<u8>()? {
+            if bytes.len() == F::BYTES {
+                return Err(de::Error::invalid_length(F::BYTES + 1, &self));
+            }
+            bytes.push(byte);
+        }
+        self.visit_bytes(&bytes)
+    }
+}
+
+pub(crate) fn deserialize_canonical<'de, D: Deserializer<'de>, F: FiniteField>(deserializer: D) -> Result<F, D::Error> {
+    deserializer.deserialize_bytes(CanonicalBytesVisitor(PhantomData))
+}
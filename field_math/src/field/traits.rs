@@ -0,0 +1,161 @@
+use std::fmt::Debug;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+
+use super::error::FieldError;
+use super::powers::Powers;
+#[cfg(feature = "rand")]
+use rand::RngCore;
+
+/// Arithmetic shared by every field we work over. Polynomials only need
+/// this much, so they are generic over `FiniteField`. The `subtle` traits
+/// give branch-free selection and comparison for code handling secrets.
+pub trait FiniteField:
+    Copy
+    + ConditionallySelectable
+    + ConstantTimeEq
+    + Debug
+    + PartialEq
+    + Eq
+    + Send
+    + Sync
+    + 'static
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+{
+    fn zero() -> Self;
+
+    fn one() -> Self;
+
+    /// Embeds an integer into the field, reducing it if needed.
+    fn from_u64(value: u64) -> Self;
+
+    fn is_zero(&self) -> bool {
+        *self == Self::zero()
+    }
+
+    /// Panics on zero; see `checked_inverse`.
+    fn inverse(&self) -> Self;
+
+    fn checked_inverse(&self) -> Result<Self, FieldError> {
+        if self.is_zero() {
+            return Err(FieldError::DivisionByZero);
+        }
+        Ok(self.inverse())
+    }
+
+    fn checked_div(&self, other: Self) -> Result<Self, FieldError> {
+        Ok(*self * other.checked_inverse()?)
+    }
+
+    fn square(&self) -> Self {
+        *self * *self
+    }
+
+    /// `1, self, self^2, ...`, each from the previous by one multiplication.
+    fn powers(&self) -> Powers<Self> {
+        Powers::new(Self::one(), *self)
+    }
+
+    /// With the `constant-time` feature, the work doesn't depend on `exponent`.
+    fn pow(&self, exponent: u64) -> Self {
+        if cfg!(feature = "constant-time") {
+            let mut result = Self::one();
+            for i in (0..u64::BITS).rev() {
+                result = result.square();
+                let product = result * *self;
+                result = Self::conditional_select(&result, &product, Choice::from(((exponent >> i) & 1) as u8));
+            }
+            return result;
+        }
+        let mut result = Self::one();
+        let mut base = *self;
+        let mut exponent = exponent;
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result * base;
+            }
+            base = base.square();
+            exponent >>= 1;
+        }
+        result
+    }
+
+    /// Length of the encoding produced by `to_bytes`.
+    const BYTES: usize;
+
+    /// Canonical fixed-width little-endian encoding of the element.
+    fn to_bytes(&self) -> Vec<u8>;
+
+    /// Inverse of `to_bytes`. Rejects encodings of the wrong length and
+    /// unreduced values, so every element has exactly one encoding.
+    fn from_bytes(bytes: &[u8]) -> Result<Self, FieldError>;
+
+    /// Hashes a byte string, e.g. a Fiat-Shamir transcript digest, to a
+    /// uniformly distributed element.
+    fn sample(byte_array: &[u8]) -> Self {
+        Self::sample_many(byte_array, 1)[0]
+    }
+
+    /// Hashes a byte string to `count` independent uniform elements.
+    fn sample_many(byte_array: &[u8], count: usize) -> Vec<Self>;
+
+    /// A uniformly random element.
+    #[cfg(feature = "rand")]
+    fn random<R: RngCore + ?Sized>(rng: &mut R) -> Self;
+
+    /// A primitive `n`-th root of unity for `n` a power of two, if the field
+    /// has one. Lets code that is generic over any field, like polynomial
+    /// multiplication, use NTTs where they are available.
+    fn power_of_two_root(_n: u64) -> Option<Self> {
+        None
+    }
+}
+
+/// A field whose multiplicative group has a large power-of-two subgroup,
+/// which is what FRI domains and NTTs are built on.
+pub trait FftField: FiniteField {
+    /// Largest `k` such that `2^k` divides the order of the multiplicative group.
+    const TWO_ADICITY: u32;
+
+    /// A primitive `2^TWO_ADICITY`-th root of unity.
+    fn two_adic_root_of_unity() -> Self;
+
+    /// A primitive `n`-th root of unity, `n` a power of two.
+    fn primitive_nth_root(n: u64) -> Self {
+        assert!(n.is_power_of_two(), "n must be a power of two");
+        let log_n = n.trailing_zeros();
+        assert!(log_n <= Self::TWO_ADICITY, "field has no root of unity of order 2^{}", log_n);
+        let mut root = Self::two_adic_root_of_unity();
+        for _ in log_n..Self::TWO_ADICITY {
+            root = root.square();
+        }
+        root
+    }
+
+    /// `primitive_nth_root`, or `None` if `n` is not a power of two or is
+    /// too large.
+    fn checked_primitive_nth_root(n: u64) -> Option<Self> {
+        (n.is_power_of_two() && n.trailing_zeros() <= Self::TWO_ADICITY).then(|| Self::primitive_nth_root(n))
+    }
+}
+
+/// A prime field `Z/pZ`.
+pub trait PrimeField: FftField {
+    /// Number of bits in the modulus.
+    const BITS: u32;
+
+    /// A generator of the multiplicative group. Being outside every proper
+    /// subgroup, it is the offset used for coset evaluation domains.
+    fn generator() -> Self;
+
+    /// Reduces a big-endian integer of any length modulo `p`.
+    fn from_bytes_wide(bytes: &[u8]) -> Self {
+        let base = Self::from_u64(256);
+        bytes.iter().fold(Self::zero(), |acc, &b| acc * base + Self::from_u64(b as u64))
+    }
+}
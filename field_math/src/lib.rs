@@ -2,4 +2,4 @@
 extern crate serde_derive;
 
 pub mod field;
-pub mod poly;
\ No newline at end of file
+pub mod poly;
@@ -0,0 +1,167 @@
+//! Lagrange interpolation over arbitrary sets of distinct points, in the
+//! barycentric form: with `Z(x) = prod_j (x - x_j)` and weights
+//! `w_i = 1 / prod_{j != i} (x_i - x_j)`, the interpolant of values `v_i` is
+//! `p(x) = Z(x) * sum_i w_i v_i / (x - x_i)`. The weights depend on the
+//! domain alone, so they are computed once and reused for every set of
+//! values and every evaluation point.
+
+use std::collections::HashSet;
+
+use crate::field::{batch_inverse, error::FieldError, traits::FiniteField};
+
+use super::uni::Uni;
+
+/// A domain of distinct points with its barycentric weights.
+#[derive(Clone, Debug)]
+pub struct BarycentricDomain<F> {
+    points: Vec<F>,
+    weights: Vec<F>,
+}
+
+impl<F: FiniteField> BarycentricDomain<F> {
+    /// Computes the weights in `O(n^2)` multiplications and one inversion.
+    pub fn new(points: Vec<F>) -> Result<Self, FieldError> {
+        check_distinct(&points)?;
+        let denominators: Vec<F> = points
+            .iter()
+            .enumerate()
+            .map(|(i, &x_i)| {
+                points.iter().enumerate().filter(|&(j, _)| j != i).fold(F::one(), |acc, (_, &x_j)| acc * (x_i - x_j))
+            })
+            .collect();
+        let weights = batch_inverse(&denominators);
+        Ok(BarycentricDomain { points, weights })
+    }
+
+    pub fn points(&self) -> &[F] {
+        &self.points
+    }
+
+    pub fn weights(&self) -> &[F] {
+        &self.weights
+    }
+
+    /// Evaluates the interpolant of `values` at `x` in `O(n)`, without
+    /// computing its coefficients. An empty domain has no second form to
+    /// evaluate and gives `DivisionByZero`.
+    pub fn evaluate(&self, values: &[F], x: F) -> Result<F, FieldError> {
+        self.check_values(values)?;
+        if let Some(i) = self.points.iter().position(|&x_i| x_i == x) {
+            return Ok(values[i]);
+        }
+        // second form: sum_i w_i v_i / (x - x_i) over sum_i w_i / (x - x_i),
+        // which needs no Z(x)
+        let differences: Vec<F> = self.points.iter().map(|&x_i| x - x_i).collect();
+        let inverses = batch_inverse(&differences);
+        let mut numerator = F::zero();
+        let mut denominator = F::zero();
+        for ((&w_i, &v_i), &d_i) in self.weights.iter().zip(values.iter()).zip(inverses.iter()) {
+            let term = w_i * d_i;
+            numerator = numerator + term * v_i;
+            denominator = denominator + term;
+        }
+        // the denominator is 1 / Z(x), which is only zero as an empty sum
+        numerator.checked_div(denominator)
+    }
+
+    /// The coefficients of the interpolant of `values`, of degree below
+    /// `n`, in `O(n^2)`.
+    pub fn interpolate(&self, values: &[F]) -> Result<Uni<F>, FieldError> {
+        self.check_values(values)?;
+        let zerofier = zerofier(&self.points);
+        let mut coefficients = vec![F::zero(); self.points.len()];
+        for ((&x_i, &w_i), &v_i) in self.points.iter().zip(self.weights.iter()).zip(values.iter()) {
+            let scale = w_i * v_i;
+            if scale.is_zero() {
+                continue;
+            }
+            // Z(x) / (x - x_i) by synthetic division, from the top
+            let mut carry = F::zero();
+            for k in (0..self.points.len()).rev() {
+                carry = zerofier[k + 1] + carry * x_i;
+                coefficients[k] = coefficients[k] + scale * carry;
+            }
+        }
+        Ok(Uni::from(coefficients))
+    }
+
+    fn check_values(&self, values: &[F]) -> Result<(), FieldError> {
+        if values.len() != self.points.len() {
+            return Err(FieldError::LengthMismatch { expected: self.points.len(), found: values.len() });
+        }
+        Ok(())
+    }
+}
+
+/// Fails with `DuplicatePoint` if any point appears twice. Elements are
+/// compared by their canonical encodings, which every field provides.
+pub(crate) fn check_distinct<F: FiniteField>(points: &[F]) -> Result<(), FieldError> {
+    let mut seen = HashSet::with_capacity(points.len());
+    for point in points {
+        if !seen.insert(point.to_bytes()) {
+            return Err(FieldError::DuplicatePoint);
+        }
+    }
+    Ok(())
+}
+
+/// The coefficients of `prod_i (x - points[i])`, one factor at a time.
+pub(crate) fn zerofier<F: FiniteField>(points: &[F]) -> Vec<F> {
+    let mut coefficients = vec![F::one()];
+    for &point in points {
+        // multiply by (x - point) in place
+        coefficients.push(F::zero());
+        for k in (0..coefficients.len()).rev() {
+            let shifted = if k > 0 { coefficients[k - 1] } else { F::zero() };
+            coefficients[k] = shifted - point * coefficients[k];
+        }
+    }
+    coefficients
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::{binary_tower::BinaryField32, field_element::Goldilocks};
+
+    fn goldilocks(values: &[u64]) -> Vec<Goldilocks> {
+        values.iter().map(|&v| Goldilocks::new(v)).collect()
+    }
+
+    #[test]
+    fn test_zerofier() {
+        // (x - 1)(x - 2) = x^2 - 3x + 2
+        assert_eq!(zerofier(&goldilocks(&[1, 2])), vec![Goldilocks::new(2), -Goldilocks::new(3), Goldilocks::one()]);
+        assert_eq!(zerofier::<Goldilocks>(&[]), vec![Goldilocks::one()]);
+    }
+
+    #[test]
+    fn test_interpolate_and_evaluate() {
+        let domain = BarycentricDomain::new(goldilocks(&[5, 17, 1 << 40, 3, 99])).unwrap();
+        let poly = Uni::from(goldilocks(&[7, 0, 11, 13, 1]));
+        let values = poly.clone().evaluate_domain(domain.points().to_vec());
+        assert_eq!(domain.interpolate(&values), Ok(poly.clone()));
+        for x in goldilocks(&[0, 17, 123456789]) {
+            assert_eq!(domain.evaluate(&values, x), Ok(poly.clone().evaluate(x)));
+        }
+    }
+
+    #[test]
+    fn test_binary_field() {
+        let domain = BarycentricDomain::new((1..=6).map(BinaryField32::new).collect()).unwrap();
+        let values: Vec<BinaryField32> = (10..16).map(BinaryField32::new).collect();
+        let poly = domain.interpolate(&values).unwrap();
+        assert_eq!(poly.evaluate_domain(domain.points().to_vec()), values);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(BarycentricDomain::new(goldilocks(&[1, 2, 1])).unwrap_err(), FieldError::DuplicatePoint);
+        let domain = BarycentricDomain::new(goldilocks(&[1, 2])).unwrap();
+        assert_eq!(domain.interpolate(&goldilocks(&[1])), Err(FieldError::LengthMismatch { expected: 2, found: 1 }));
+        assert_eq!(domain.evaluate(&goldilocks(&[1, 2, 3]), Goldilocks::zero()), Err(FieldError::LengthMismatch { expected: 2, found: 3 }));
+
+        let empty = BarycentricDomain::<Goldilocks>::new(vec![]).unwrap();
+        assert_eq!(empty.evaluate(&[], Goldilocks::one()), Err(FieldError::DivisionByZero));
+    }
+}
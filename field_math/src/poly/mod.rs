@@ -1,2 +1,42 @@
+use std::fmt::{self, Display};
+
+use crate::field::traits::FiniteField;
+
 pub mod uni;
-pub mod multi;
\ No newline at end of file
+pub mod barycentric;
+pub mod multi;
+pub mod multiply;
+pub mod ntt;
+pub mod subproduct_tree;
+
+/// Writes a sum of `(coefficient, monomial)` terms, e.g. "3x^2 - x + 1",
+/// using an empty monomial for the constant term. Zero terms are skipped and
+/// unit coefficients left out; with `{:#}` coefficients print signed.
+fn write_terms<F: FiniteField + Display>(f: &mut fmt::Formatter<'_>, terms: impl IntoIterator<Item = (F, String)>) -> fmt::Result {
+    let mut first = true;
+    for (coefficient, monomial) in terms {
+        if coefficient.is_zero() {
+            continue;
+        }
+        let coefficient = if f.alternate() { format!("{:#}", coefficient) } else { coefficient.to_string() };
+        let (negative, magnitude) = match coefficient.strip_prefix('-') {
+            Some(magnitude) => (true, magnitude),
+            None => (false, coefficient.as_str()),
+        };
+        match (first, negative) {
+            (true, true) => write!(f, "-")?,
+            (true, false) => {}
+            (false, true) => write!(f, " - ")?,
+            (false, false) => write!(f, " + ")?,
+        }
+        if monomial.is_empty() || magnitude != "1" {
+            write!(f, "{}", magnitude)?;
+        }
+        write!(f, "{}", monomial)?;
+        first = false;
+    }
+    if first {
+        write!(f, "0")?;
+    }
+    Ok(())
+}
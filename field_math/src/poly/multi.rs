@@ -1,17 +1,20 @@
 use std::collections::HashMap;
+use std::fmt;
 use std::ops::{Add, Mul, Neg, Sub};
-use crate::field::field_element::FieldElement;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::field::traits::FiniteField;
+use super::write_terms;
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Exponents(pub Vec<i32>);
 
 #[derive(Debug, Clone)]
-pub struct MPolynomial<'a> {
-    pub dictionary: HashMap<Exponents, FieldElement<'a>>,
+pub struct MPolynomial<F: FiniteField> {
+    pub dictionary: HashMap<Exponents, F>,
 }
 
-impl<'a> MPolynomial<'a> {
-    pub fn new(dictionary: HashMap<Exponents, FieldElement<'a>>) -> Self {
+impl<F: FiniteField> MPolynomial<F> {
+    pub fn new(dictionary: HashMap<Exponents, F>) -> Self {
         MPolynomial { dictionary }
     }
 
@@ -21,13 +24,13 @@ impl<'a> MPolynomial<'a> {
         }
     }
 
-    fn constant(element: FieldElement<'a>) -> Self {
+    pub fn constant(element: F) -> Self {
         let mut dictionary = HashMap::new();
         dictionary.insert(Exponents(vec![0]), element);
         MPolynomial { dictionary }
     }
 
-    fn is_zero(&self) -> bool {
+    pub fn is_zero(&self) -> bool {
         if self.dictionary.is_empty() {
             return true;
         }
@@ -39,43 +42,67 @@ impl<'a> MPolynomial<'a> {
         true
     }
 
-    fn variables(num_variables: usize, one: FieldElement<'a>) -> Vec<Self> {
+    pub fn variables(num_variables: usize, one: F) -> Vec<Self> {
         let mut variables = Vec::new();
         for i in 0..num_variables {
             let mut exponent = vec![0; num_variables];
             exponent[i] = 1;
             let mut dictionary = HashMap::new();
-            dictionary.insert(Exponents(exponent), one.clone());
+            dictionary.insert(Exponents(exponent), one);
             variables.push(MPolynomial { dictionary });
         }
         variables
     }
 
+    /// A polynomial in `num_variables` variables with a uniformly random
+    /// coefficient for every monomial of total degree at most `max_degree`.
+    #[cfg(feature = "rand")]
+    pub fn random<R: rand::RngCore + ?Sized>(num_variables: usize, max_degree: usize, rng: &mut R) -> Self {
+        // all exponent vectors, built one variable at a time
+        let mut monomials: Vec<(Vec<i32>, usize)> = vec![(vec![], 0)];
+        for _ in 0..num_variables {
+            monomials = monomials
+                .into_iter()
+                .flat_map(|(exponents, degree)| {
+                    (0..=max_degree - degree).map(move |e| {
+                        let mut exponents = exponents.clone();
+                        exponents.push(e as i32);
+                        (exponents, degree + e)
+                    })
+                })
+                .collect();
+        }
+        let dictionary = monomials
+            .into_iter()
+            .map(|(exponents, _)| (Exponents(exponents), F::random(rng)))
+            .collect();
+        MPolynomial { dictionary }
+    }
+
     fn pad_exponents(&self, exponents: &Exponents, num_variables: usize) -> Exponents {
         let mut pad = exponents.0.clone();
         pad.resize(num_variables, 0);
         Exponents(pad)
     }
 
-    fn evaluate(&self, values: &[FieldElement<'a>]) -> FieldElement<'a> {
-        let field = values[0].field;    
-        let mut result = FieldElement::from(0, values[0].field);
+    pub fn evaluate(&self, values: &[F]) -> F {
+        let mut result = F::zero();
         for (exponents, coefficient) in self.dictionary.iter() {
-            let mut term = coefficient.clone();
+            let mut term = *coefficient;
             for (i, &e) in exponents.0.iter().enumerate() {
-                term = term * values[i].clone() ^ FieldElement::from(e as i128, field );
+                term = term * values[i].pow(e as u64);
             }
             result = result + term;
         }
         result
     }
 
-    fn evaluate_symbolic(&self, values: &[FieldElement<'a>]) -> Self {
+    pub fn evaluate_symbolic(&self, values: &[F]) -> Self {
         let mut dictionary = HashMap::new();
         for (exponents, coefficient) in self.dictionary.iter() {
-            let mut term = coefficient.clone();
+            let mut term = *coefficient;
             for (i, &e) in exponents.0.iter().enumerate() {
-                term = term * (values[i].clone() ^ FieldElement::from(e as i128, values[i].field));
+                term = term * values[i].pow(e as u64);
             }
             dictionary.insert(exponents.clone(), term);
         }
@@ -83,7 +110,42 @@ impl<'a> MPolynomial<'a> {
     }
 }
 
-impl<'a> Add for MPolynomial<'a> {
+// Serialized as a list of (exponents, coefficient) terms sorted by
+// exponents, so the encoding doesn't depend on hash map order and works for
+// formats whose map keys must be strings.
+impl<F: FiniteField + Serialize> Serialize for MPolynomial<F> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut terms: Vec<(&Exponents, &F)> = self.dictionary.iter().collect();
+        terms.sort_by(|a, b| a.0.cmp(b.0));
+        serializer.collect_seq(terms)
+    }
+}
+
+impl<'de, F: FiniteField + Deserialize<'de>> Deserialize<'de> for MPolynomial<F> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let terms: Vec<(Exponents, F)> = Vec::deserialize(deserializer)?;
+        Ok(MPolynomial { dictionary: terms.into_iter().collect() })
+    }
+}
+
+/// Terms in decreasing order of their exponents, with variables named by
+/// index, e.g. "3x0^2*x1 + x1 - 5" (signed coefficients with `{:#}`).
+impl<F: FiniteField + fmt::Display> fmt::Display for MPolynomial<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut terms: Vec<(&Exponents, &F)> = self.dictionary.iter().collect();
+        terms.sort_by(|a, b| b.0.cmp(a.0));
+        let terms = terms.into_iter().map(|(exponents, &coefficient)| {
+            let monomial: Vec<String> = exponents.0.iter().enumerate()
+                .filter(|(_, &e)| e != 0)
+                .map(|(i, &e)| if e == 1 { format!("x{}", i) } else { format!("x{}^{}", i, e) })
+                .collect();
+            (coefficient, monomial.join("*"))
+        });
+        write_terms(f, terms)
+    }
+}
+
+impl<F: FiniteField> Add for MPolynomial<F> {
     type Output = Self;
 
     fn add(self, other: Self) -> Self {
@@ -98,14 +160,14 @@ impl<'a> Add for MPolynomial<'a> {
 
         for (k, v) in other.clone().dictionary {
             let pad = other.pad_exponents(&k, num_variables);
-            dictionary.entry(pad).and_modify(|e| *e = e.clone() + v.clone()).or_insert(v);
+            dictionary.entry(pad).and_modify(|e| *e = *e + v).or_insert(v);
         }
 
         MPolynomial { dictionary }
     }
 }
 
-impl<'a> Mul for MPolynomial<'a> {
+impl<F: FiniteField> Mul for MPolynomial<F> {
     type Output = Self;
 
     fn mul(self, other: Self) -> Self {
@@ -123,7 +185,7 @@ impl<'a> Mul for MPolynomial<'a> {
                     exponent[i] += e;
                 }
                 let exponent = Exponents(exponent);
-                dictionary.entry(exponent).and_modify(|e: &mut FieldElement<'a>| *e = e.clone() + v0.clone() * v1.clone()).or_insert(v0.clone() * v1.clone());
+                dictionary.entry(exponent).and_modify(|e: &mut F| *e = *e + *v0 * *v1).or_insert(*v0 * *v1);
             }
         }
 
@@ -131,7 +193,7 @@ impl<'a> Mul for MPolynomial<'a> {
     }
 }
 
-impl<'a> Neg for MPolynomial<'a> {
+impl<F: FiniteField> Neg for MPolynomial<F> {
     type Output = Self;
 
     fn neg(self) -> Self {
@@ -143,7 +205,7 @@ impl<'a> Neg for MPolynomial<'a> {
     }
 }
 
-impl<'a> Sub for MPolynomial<'a> {
+impl<F: FiniteField> Sub for MPolynomial<F> {
     type Output = Self;
 
     fn sub(self, other: Self) -> Self {
@@ -151,15 +213,13 @@ impl<'a> Sub for MPolynomial<'a> {
     }
 }
 
-impl<'a> MPolynomial<'a> {
-    fn pow(&self, exponent: u32) -> Self {
+impl<F: FiniteField> MPolynomial<F> {
+    pub fn pow(&self, exponent: u32) -> Self {
         if self.is_zero() {
             return MPolynomial::zero();
         }
 
-        let one = self.dictionary.values().next().cloned().unwrap();
-        let num_variables = self.dictionary.keys().next().map_or(0, |k| k.0.len());
-        let mut acc = MPolynomial::constant(one);
+        let mut acc = MPolynomial::constant(F::one());
 
         for b in format!("{:b}", exponent).chars() {
             acc = acc.clone() * acc.clone();
@@ -175,29 +235,76 @@ impl<'a> MPolynomial<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::field::field::Field;
+    use crate::field::{field::Field, field_element::FieldElement, monty31::BabyBearQuartic, DEFAULT_PRIME};
 
     #[test]
     fn test_monomial() {
         let field = Field::new();
         let one = field.one();
         let x = MPolynomial::new(
-            [(Exponents(vec![1]), one.clone())]
+            [(Exponents(vec![1]), one)]
                 .iter()
                 .cloned()
                 .collect(),
         );
         let x2 = x.clone() * x.clone();
-        let x3 = x.clone() * x2.clone();
+        let _x3 = x.clone() * x2.clone();
         let x4 = x2.clone() * x2.clone();
-        let x5 = x.clone() * x4.clone();
+        let _x5 = x.clone() * x4.clone();
         let x6 = x2.clone() * x4.clone();
-        let x7 = x.clone() * x6.clone();
+        let _x7 = x.clone() * x6.clone();
         let x8 = x2.clone() * x6.clone();
-        let x9 = x.clone() * x8.clone();
+        let _x9 = x.clone() * x8.clone();
 
         dbg!(&x);
 
-        assert_eq!(x.evaluate(&[one.clone()]), one.clone());
+        assert_eq!(x.evaluate(&[one]), one.clone());
+    }
+
+    #[test]
+    fn test_display() {
+        let field = Field::new();
+        let [x, y]: [MPolynomial<FieldElement>; 2] = MPolynomial::variables(2, field.one()).try_into().unwrap();
+        let three = MPolynomial::constant(FieldElement::new(3));
+        let polynomial = three * x.clone() * x * y.clone() + y - MPolynomial::constant(FieldElement::new(5));
+        assert_eq!(format!("{:#}", polynomial), "3x0^2*x1 + x1 - 5");
+        assert_eq!(MPolynomial::<FieldElement>::zero().to_string(), "0");
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_random() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let polynomial: MPolynomial<FieldElement> = MPolynomial::random(3, 2, &mut rng);
+        // 1 + 3 + 6 monomials of degree 0, 1 and 2
+        assert_eq!(polynomial.dictionary.len(), 10);
+        assert!(polynomial.dictionary.keys().all(|e| e.0.iter().sum::<i32>() <= 2));
+
+        let point = [FieldElement::new(2), FieldElement::new(3), FieldElement::new(5)];
+        let doubled = polynomial.clone() + polynomial.clone();
+        assert_eq!(doubled.evaluate(&point), polynomial.evaluate(&point) * FieldElement::new(2));
+    }
+
+    #[test]
+    fn test_serde_roundtrip() {
+        let field = Field::new();
+        let [x, y]: [MPolynomial<FieldElement>; 2] = MPolynomial::variables(2, field.one()).try_into().unwrap();
+        let polynomial = x.clone() * y + x + MPolynomial::constant(FieldElement::new(5));
+        let bytes = serde_pickle::to_vec(&polynomial, Default::default()).unwrap();
+        let decoded: MPolynomial<FieldElement> = serde_pickle::from_slice(&bytes, Default::default()).unwrap();
+        assert_eq!(decoded.dictionary, polynomial.dictionary);
+        assert_eq!(serde_pickle::to_vec(&decoded, Default::default()).unwrap(), bytes);
+
+        let [u, v]: [MPolynomial<BabyBearQuartic>; 2] = MPolynomial::variables(2, BabyBearQuartic::one()).try_into().unwrap();
+        let polynomial = u.clone() * u * v + MPolynomial::constant(BabyBearQuartic::sample(b"constant"));
+        let bytes = serde_pickle::to_vec(&polynomial, Default::default()).unwrap();
+        let decoded: MPolynomial<BabyBearQuartic> = serde_pickle::from_slice(&bytes, Default::default()).unwrap();
+        assert_eq!(decoded.dictionary, polynomial.dictionary);
+
+        // a coefficient equal to p is not reduced
+        let unreduced = serde_pickle::to_vec(&vec![(Exponents(vec![1, 0]), DEFAULT_PRIME.to_le_bytes())], Default::default()).unwrap();
+        assert!(serde_pickle::from_slice::<MPolynomial<FieldElement>>(&unreduced, Default::default()).is_err());
     }
 }
\ No newline at end of file
@@ -0,0 +1,148 @@
+//! Multiplication of coefficient vectors, choosing the algorithm by size:
+//! schoolbook for short operands, NTT convolution when the field has roots
+//! of unity of the needed order, and Karatsuba otherwise.
+
+use crate::field::traits::FiniteField;
+
+use super::ntt::Twiddles;
+
+/// Below this many coefficients in the shorter operand, the schoolbook
+/// product is the fastest.
+const KARATSUBA_THRESHOLD: usize = 32;
+
+/// From this many coefficients in the shorter operand on, three NTTs beat
+/// Karatsuba.
+const NTT_THRESHOLD: usize = 64;
+
+/// The coefficients of the product of the polynomials with coefficients
+/// `a` and `b`, of length `a.len() + b.len() - 1`, or empty if either is.
+pub fn multiply<F: FiniteField>(a: &[F], b: &[F]) -> Vec<F> {
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+    let shorter = a.len().min(b.len());
+    if shorter < KARATSUBA_THRESHOLD {
+        return schoolbook(a, b);
+    }
+    if shorter >= NTT_THRESHOLD {
+        if let Some(product) = ntt_multiply(a, b) {
+            return product;
+        }
+    }
+    karatsuba(a, b)
+}
+
+fn schoolbook<F: FiniteField>(a: &[F], b: &[F]) -> Vec<F> {
+    let mut product = vec![F::zero(); a.len() + b.len() - 1];
+    for (i, &x) in a.iter().enumerate() {
+        for (j, &y) in b.iter().enumerate() {
+            product[i + j] = product[i + j] + x * y;
+        }
+    }
+    product
+}
+
+/// Splits both operands at half the longer one, `a = a0 + x^m a1`, and
+/// recovers the middle term from one product: `a0 b1 + a1 b0 =
+/// (a0 + a1)(b0 + b1) - a0 b0 - a1 b1`.
+fn karatsuba<F: FiniteField>(a: &[F], b: &[F]) -> Vec<F> {
+    let m = a.len().max(b.len()).div_ceil(2);
+    let (a0, a1) = a.split_at(m.min(a.len()));
+    let (b0, b1) = b.split_at(m.min(b.len()));
+
+    let z0 = multiply(a0, b0);
+    let z2 = multiply(a1, b1);
+    let mut z1 = multiply(&add(a0, a1), &add(b0, b1));
+    for (i, &z) in z0.iter().enumerate() {
+        z1[i] = z1[i] - z;
+    }
+    for (i, &z) in z2.iter().enumerate() {
+        z1[i] = z1[i] - z;
+    }
+
+    let mut product = vec![F::zero(); a.len() + b.len() - 1];
+    for (i, &z) in z0.iter().enumerate() {
+        product[i] = product[i] + z;
+    }
+    // z1 may carry zero high coefficients past the end of the product
+    for (i, &z) in z1.iter().enumerate().take(product.len() - m) {
+        product[i + m] = product[i + m] + z;
+    }
+    for (i, &z) in z2.iter().enumerate() {
+        product[i + 2 * m] = product[i + 2 * m] + z;
+    }
+    product
+}
+
+/// Coefficient-wise sum of vectors of possibly different lengths.
+fn add<F: FiniteField>(a: &[F], b: &[F]) -> Vec<F> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut sum = long.to_vec();
+    for (s, &x) in sum.iter_mut().zip(short.iter()) {
+        *s = *s + x;
+    }
+    sum
+}
+
+/// Cyclic convolution of size a power of two at least the product length,
+/// so nothing wraps around. `None` if the field lacks the root of unity.
+fn ntt_multiply<F: FiniteField>(a: &[F], b: &[F]) -> Option<Vec<F>> {
+    let length = a.len() + b.len() - 1;
+    let size = length.next_power_of_two();
+    let twiddles = Twiddles::new(F::power_of_two_root(size as u64)?, size);
+
+    let mut a_values = a.to_vec();
+    a_values.resize(size, F::zero());
+    let mut b_values = b.to_vec();
+    b_values.resize(size, F::zero());
+    twiddles.ntt(&mut a_values);
+    twiddles.ntt(&mut b_values);
+    for (x, &y) in a_values.iter_mut().zip(b_values.iter()) {
+        *x = *x * y;
+    }
+    twiddles.intt(&mut a_values);
+    a_values.truncate(length);
+    Some(a_values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::{binary_tower::BinaryField64, field_element::Goldilocks, monty31::BabyBear};
+
+    fn elements<F: FiniteField>(seed: &[u8], count: usize) -> Vec<F> {
+        F::sample_many(seed, count)
+    }
+
+    #[test]
+    fn test_algorithms_agree() {
+        for (m, n) in [(1, 1), (3, 70), (33, 40), (64, 64), (100, 37), (200, 129)] {
+            let a: Vec<Goldilocks> = elements(b"a", m);
+            let b: Vec<Goldilocks> = elements(b"b", n);
+            let expected = schoolbook(&a, &b);
+            assert_eq!(karatsuba(&a, &b), expected, "karatsuba {}x{}", m, n);
+            assert_eq!(ntt_multiply(&a, &b), Some(expected.clone()), "ntt {}x{}", m, n);
+            assert_eq!(multiply(&a, &b), expected, "multiply {}x{}", m, n);
+        }
+    }
+
+    #[test]
+    fn test_fields_without_large_roots() {
+        // GF(2^64) has no roots of unity of even order at all, and BabyBear
+        // none of order above 2^27; both still multiply
+        let a: Vec<BinaryField64> = elements(b"a", 100);
+        let b: Vec<BinaryField64> = elements(b"b", 90);
+        assert_eq!(BinaryField64::power_of_two_root(256), None);
+        assert_eq!(multiply(&a, &b), schoolbook(&a, &b));
+
+        assert!(BabyBear::power_of_two_root(1 << 27).is_some());
+        assert_eq!(BabyBear::power_of_two_root(1 << 28), None);
+    }
+
+    #[test]
+    fn test_empty_operands() {
+        let a: Vec<Goldilocks> = elements(b"a", 4);
+        assert!(multiply(&a, &[]).is_empty());
+        assert!(multiply::<Goldilocks>(&[], &[]).is_empty());
+    }
+}
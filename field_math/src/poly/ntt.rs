@@ -0,0 +1,139 @@
+//! Radix-2 number theoretic transform: evaluating a polynomial of degree
+//! below `n` on the subgroup generated by an `n`-th root of unity, and its
+//! inverse, in `O(n log n)` field operations for `n` a power of two.
+
+use crate::field::traits::FiniteField;
+
+/// The powers `root^i` for `i < n / 2` of a primitive `n`-th root of unity,
+/// shared by every butterfly layer of a size-`n` transform.
+#[derive(Clone, Debug)]
+pub struct Twiddles<F> {
+    size: usize,
+    powers: Vec<F>,
+}
+
+impl<F: FiniteField> Twiddles<F> {
+    /// Panics unless `size` is a power of two and `root` has order exactly
+    /// `size`.
+    pub fn new(root: F, size: usize) -> Self {
+        assert!(size.is_power_of_two(), "NTT size must be a power of two, got {}", size);
+        assert!(
+            root.pow(size as u64) == F::one() && (size == 1 || root.pow(size as u64 / 2) != F::one()),
+            "{:?} is not a primitive {}-th root of unity", root, size
+        );
+        Twiddles { size, powers: root.powers().take(size / 2).collect() }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Replaces the coefficients `values[i]` of `x^i` with the evaluations
+    /// at `root^i`, both in natural order.
+    pub fn ntt(&self, values: &mut [F]) {
+        assert_eq!(values.len(), self.size, "NTT input length does not match the twiddles");
+        bit_reverse_permutation(values);
+        // iterative Cooley-Tukey: merge transforms of size len / 2 into len
+        let mut len = 2;
+        while len <= self.size {
+            let stride = self.size / len;
+            for chunk in values.chunks_exact_mut(len) {
+                let (low, high) = chunk.split_at_mut(len / 2);
+                for (j, (a, b)) in low.iter_mut().zip(high.iter_mut()).enumerate() {
+                    let t = *b * self.powers[j * stride];
+                    *b = *a - t;
+                    *a = *a + t;
+                }
+            }
+            len *= 2;
+        }
+    }
+
+    /// The inverse of `ntt`: evaluations at `root^i` back to coefficients.
+    pub fn intt(&self, values: &mut [F]) {
+        // transforming with root^-1 equals transforming with root and
+        // reading the outputs at -i mod n
+        self.ntt(values);
+        values[1..].reverse();
+        let size_inverse = F::from_u64(self.size as u64).inverse();
+        for value in values.iter_mut() {
+            *value = *value * size_inverse;
+        }
+    }
+}
+
+/// Permutes `values`, whose length is a power of two, so that index `i`
+/// moves to the index with the bits of `i` reversed.
+pub fn bit_reverse_permutation<T>(values: &mut [T]) {
+    let n = values.len();
+    if n <= 2 {
+        return;
+    }
+    let shift = usize::BITS - n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> shift;
+        if i < j {
+            values.swap(i, j);
+        }
+    }
+}
+
+/// Evaluates the polynomial with coefficients `values` at the powers of
+/// `root`, in place; see `Twiddles::ntt`.
+pub fn ntt<F: FiniteField>(values: &mut [F], root: F) {
+    Twiddles::new(root, values.len()).ntt(values)
+}
+
+/// Interpolates evaluations at the powers of `root`, in place; see
+/// `Twiddles::intt`.
+pub fn intt<F: FiniteField>(values: &mut [F], root: F) {
+    Twiddles::new(root, values.len()).intt(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::{field_element::Goldilocks, monty31::BabyBear, traits::FftField};
+    use crate::poly::uni::Uni;
+
+    #[test]
+    fn test_bit_reverse_permutation() {
+        let mut values: Vec<usize> = (0..8).collect();
+        bit_reverse_permutation(&mut values);
+        assert_eq!(values, vec![0, 4, 2, 6, 1, 5, 3, 7]);
+    }
+
+    #[test]
+    fn test_ntt_matches_evaluation() {
+        let n = 64;
+        let root = Goldilocks::primitive_nth_root(n as u64);
+        let coefficients: Vec<Goldilocks> = (0..n as u64).map(|i| Goldilocks::new(i * i + 7)).collect();
+        let mut values = coefficients.clone();
+        ntt(&mut values, root);
+        let expected = Uni::from(coefficients.clone()).evaluate_domain(root.powers().take(n).collect());
+        assert_eq!(values, expected);
+
+        intt(&mut values, root);
+        assert_eq!(values, coefficients);
+    }
+
+    #[test]
+    fn test_small_sizes() {
+        let mut one = vec![BabyBear::new(5)];
+        ntt(&mut one, BabyBear::one());
+        assert_eq!(one, vec![BabyBear::new(5)]);
+
+        let mut two = vec![BabyBear::new(1), BabyBear::new(2)];
+        ntt(&mut two, -BabyBear::one());
+        assert_eq!(two, vec![BabyBear::new(3), -BabyBear::one()]);
+        intt(&mut two, -BabyBear::one());
+        assert_eq!(two, vec![BabyBear::new(1), BabyBear::new(2)]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_root_must_be_primitive() {
+        // 1 has order 1, not 4
+        ntt(&mut [Goldilocks::one(); 4], Goldilocks::one());
+    }
+}
@@ -0,0 +1,259 @@
+//! Fast multipoint evaluation and interpolation over arbitrary domains.
+//! The subproduct tree of points `x_0, ..., x_{n-1}` has the linear factors
+//! `x - x_i` as leaves and the product of its children at every other node,
+//! so the root is the zerofier of the domain. Reducing a polynomial down the
+//! tree evaluates it at every point, and combining weighted values up the
+//! tree interpolates, both in `O(M(n) log n)` for `M(n)` the cost of
+//! multiplying polynomials of degree `n`.
+
+use crate::field::{batch_inverse, error::FieldError, traits::FiniteField};
+
+use super::{multiply::multiply, uni::Uni};
+
+/// From this many points and coefficients on, reducing down the tree beats
+/// Horner's rule at every point.
+pub(crate) const TREE_EVALUATION_THRESHOLD: usize = 2048;
+
+/// From this many points on, the tree beats `O(n^2)` interpolation and
+/// zerofier products.
+pub(crate) const TREE_INTERPOLATION_THRESHOLD: usize = 256;
+
+/// Below this many quotient or divisor coefficients, long division beats
+/// dividing via a power series inverse.
+const DIVISION_THRESHOLD: usize = 64;
+
+/// A subproduct tree; `levels[0]` holds the leaves and the last level the
+/// root. Node `j` of a level is the product of nodes `2j` and `2j + 1` of
+/// the level below, or a copy of node `2j` if that is the last one.
+#[derive(Clone, Debug)]
+pub struct SubproductTree<F: FiniteField> {
+    points: Vec<F>,
+    levels: Vec<Vec<Uni<F>>>,
+}
+
+impl<F: FiniteField> SubproductTree<F> {
+    pub fn new(points: &[F]) -> Self {
+        let mut levels = vec![points.iter().map(|&x| Uni::from(vec![-x, F::one()])).collect::<Vec<_>>()];
+        while levels.last().unwrap().len() > 1 {
+            let next = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => Uni::from(multiply(&left.coefficients, &right.coefficients)),
+                    [single] => single.clone(),
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+        SubproductTree { points: points.to_vec(), levels }
+    }
+
+    pub fn points(&self) -> &[F] {
+        &self.points
+    }
+
+    /// `prod_i (x - x_i)`, the root of the tree.
+    pub fn zerofier(&self) -> Uni<F> {
+        match self.levels.last().unwrap().first() {
+            Some(root) => root.clone(),
+            None => Uni::from(vec![F::one()]),
+        }
+    }
+
+    /// The values of `poly` at every point: the remainder of `poly` modulo
+    /// a node is its remainder modulo each child, down to the leaves where
+    /// it is a constant, `poly(x_i)`.
+    pub fn evaluate(&self, poly: &Uni<F>) -> Vec<F> {
+        if self.points.is_empty() {
+            return vec![];
+        }
+        let top = self.levels.len() - 1;
+        let mut remainders = vec![remainder(&poly.coefficients, &self.levels[top][0].coefficients)];
+        for level in (0..top).rev() {
+            remainders = self.levels[level]
+                .iter()
+                .enumerate()
+                .map(|(i, node)| remainder(&remainders[i / 2], &node.coefficients))
+                .collect();
+        }
+        remainders.into_iter().map(|r| r.first().copied().unwrap_or(F::zero())).collect()
+    }
+
+    /// The polynomial of degree below `n` taking `values[i]` at `x_i`. With
+    /// `Z` the root, it is `sum_i v_i / Z'(x_i) * Z(x) / (x - x_i)`, and
+    /// each node contributes its left sum times its right factor plus its
+    /// right sum times its left factor. `Z'(x_i)` is zero exactly when a
+    /// point repeats.
+    pub fn interpolate(&self, values: &[F]) -> Result<Uni<F>, FieldError> {
+        if values.len() != self.points.len() {
+            return Err(FieldError::LengthMismatch { expected: self.points.len(), found: values.len() });
+        }
+        if self.points.is_empty() {
+            return Ok(Uni::from(vec![]));
+        }
+        let derivative_values = self.evaluate(&derivative(&self.zerofier()));
+        if derivative_values.iter().any(|d| d.is_zero()) {
+            return Err(FieldError::DuplicatePoint);
+        }
+        let weights = batch_inverse(&derivative_values);
+
+        let mut sums: Vec<Vec<F>> = values.iter().zip(weights.iter()).map(|(&v, &w)| vec![v * w]).collect();
+        for level in 0..self.levels.len() - 1 {
+            let nodes = &self.levels[level];
+            sums = sums
+                .chunks(2)
+                .enumerate()
+                .map(|(j, pair)| match pair {
+                    [left, right] => {
+                        let mut sum = multiply(left, &nodes[2 * j + 1].coefficients);
+                        let other = multiply(right, &nodes[2 * j].coefficients);
+                        if other.len() > sum.len() {
+                            sum.resize(other.len(), F::zero());
+                        }
+                        for (s, o) in sum.iter_mut().zip(other) {
+                            *s = *s + o;
+                        }
+                        sum
+                    }
+                    [single] => single.clone(),
+                    _ => unreachable!(),
+                })
+                .collect();
+        }
+        Ok(Uni::from(sums.pop().unwrap()))
+    }
+}
+
+/// The formal derivative.
+fn derivative<F: FiniteField>(poly: &Uni<F>) -> Uni<F> {
+    Uni::from(
+        poly.coefficients
+            .iter()
+            .enumerate()
+            .skip(1)
+            .map(|(i, &c)| c * F::from_u64(i as u64))
+            .collect(),
+    )
+}
+
+/// `a mod b` for monic `b`. Large divisions compute the quotient from the
+/// reversed polynomials, `rev(q) = rev(a) / rev(b) mod x^(deg a - deg b + 1)`,
+/// which costs a couple of multiplications instead of `O(n^2)`.
+fn remainder<F: FiniteField>(a: &[F], b: &[F]) -> Vec<F> {
+    let m = b.len() - 1;
+    let a = &a[..a.iter().rposition(|c| !c.is_zero()).map_or(0, |i| i + 1)];
+    if a.len() <= m {
+        return a.to_vec();
+    }
+    let k = a.len() - m;
+
+    if m < DIVISION_THRESHOLD || k < DIVISION_THRESHOLD {
+        let mut r = a.to_vec();
+        for i in (0..k).rev() {
+            let coefficient = r[i + m];
+            for (j, &b_j) in b.iter().enumerate() {
+                r[i + j] = r[i + j] - coefficient * b_j;
+            }
+        }
+        r.truncate(m);
+        return r;
+    }
+
+    let reversed_b: Vec<F> = b.iter().rev().copied().collect();
+    let reversed_a: Vec<F> = a.iter().rev().take(k).copied().collect();
+    let mut reversed_quotient = multiply(&reversed_a, &series_inverse(&reversed_b, k));
+    reversed_quotient.truncate(k);
+    let quotient: Vec<F> = reversed_quotient.into_iter().rev().collect();
+    let product = multiply(&quotient, b);
+    a[..m].iter().zip(product.iter()).map(|(&x, &y)| x - y).collect()
+}
+
+/// `g` with `f * g = 1 mod x^k`, by Newton's iteration `g <- g (2 - f g)`,
+/// which doubles the number of correct coefficients each step. `f[0]` must
+/// be invertible.
+fn series_inverse<F: FiniteField>(f: &[F], k: usize) -> Vec<F> {
+    let mut g = vec![f[0].inverse()];
+    let mut precision = 1;
+    while precision < k {
+        precision *= 2;
+        let mut error = multiply(&f[..precision.min(f.len())], &g);
+        error.truncate(precision);
+        for e in error.iter_mut() {
+            *e = -*e;
+        }
+        error[0] = error[0] + F::from_u64(2);
+        g = multiply(&g, &error);
+        g.truncate(precision);
+    }
+    g.truncate(k);
+    g
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::{binary_tower::BinaryField64, field_element::Goldilocks};
+    use crate::poly::barycentric::zerofier;
+
+    fn points(count: usize) -> Vec<Goldilocks> {
+        // distinct, and no subgroup structure
+        (0..count as u64).map(|i| Goldilocks::new(i * i * 7 + 3 * i + 11)).collect()
+    }
+
+    #[test]
+    fn test_zerofier() {
+        let domain = points(37);
+        let tree = SubproductTree::new(&domain);
+        assert_eq!(tree.zerofier(), Uni::from(zerofier(&domain)));
+        assert_eq!(SubproductTree::<Goldilocks>::new(&[]).zerofier(), Uni::from(vec![Goldilocks::one()]));
+    }
+
+    #[test]
+    fn test_remainder() {
+        let a = Goldilocks::new(3).powers().take(500).collect::<Vec<_>>();
+        let b = zerofier(&points(150));
+        let (_, expected) = Uni::from(a.clone()).true_division(Uni::from(b.clone())).unwrap();
+        assert_eq!(Uni::from(remainder(&a, &b)), expected);
+    }
+
+    #[test]
+    fn test_series_inverse() {
+        let f: Vec<Goldilocks> = points(10);
+        let g = series_inverse(&f, 100);
+        let mut product = multiply(&f, &g);
+        product.truncate(100);
+        let mut one = vec![Goldilocks::zero(); 100];
+        one[0] = Goldilocks::one();
+        assert_eq!(product, one);
+    }
+
+    #[test]
+    fn test_evaluate_and_interpolate() {
+        for count in [1, 2, 5, 100, 300] {
+            let domain = points(count);
+            let tree = SubproductTree::new(&domain);
+            let poly = Uni::from(Goldilocks::new(5).powers().take(count).collect());
+            let values = tree.evaluate(&poly);
+            assert_eq!(values, domain.iter().map(|&x| poly.clone().evaluate(x)).collect::<Vec<_>>(), "{} points", count);
+            assert_eq!(tree.interpolate(&values), Ok(poly), "{} points", count);
+        }
+    }
+
+    #[test]
+    fn test_binary_field() {
+        let domain: Vec<BinaryField64> = (1..=70).map(|i| BinaryField64::new(i * 0x9e37_79b9)).collect();
+        let tree = SubproductTree::new(&domain);
+        let values: Vec<BinaryField64> = (0..70).map(BinaryField64::new).collect();
+        let poly = tree.interpolate(&values).unwrap();
+        assert_eq!(tree.evaluate(&poly), values);
+    }
+
+    #[test]
+    fn test_errors() {
+        let tree = SubproductTree::new(&[Goldilocks::new(1), Goldilocks::new(2), Goldilocks::new(1)]);
+        assert_eq!(tree.interpolate(&[Goldilocks::one(); 3]), Err(FieldError::DuplicatePoint));
+        assert_eq!(tree.interpolate(&[Goldilocks::one(); 2]), Err(FieldError::LengthMismatch { expected: 3, found: 2 }));
+    }
+}
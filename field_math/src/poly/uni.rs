@@ -1,158 +1,225 @@
-use std::{collections::HashMap, ops::{Add, BitXor, Div, Mul, Rem, Sub}};
-
-use crate::field::field_element::FieldElement;
-
-use super::multi::{Exponents, MPolynomial};
-
-/// This is the Univariate polynomial struct 
-/// 
-
-#[derive(Debug, Clone)]
-pub struct Uni<'a>{
-    pub coefficients : Vec<FieldElement<'a>>
+use std::{collections::HashMap, fmt, ops::{Add, BitXor, Div, Mul, Rem, Sub}};
+
+use crate::field::{error::FieldError, packed, traits::{FftField, FiniteField}};
+
+use super::{
+    barycentric::{check_distinct, zerofier, BarycentricDomain},
+    multi::{Exponents, MPolynomial},
+    multiply::multiply,
+    ntt::Twiddles,
+    subproduct_tree::{SubproductTree, TREE_EVALUATION_THRESHOLD, TREE_INTERPOLATION_THRESHOLD},
+    write_terms,
+};
+
+/// This is the Univariate polynomial struct; `coefficients[i]` is the
+/// coefficient of `x^i`. Serializes as the plain list of coefficients.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Uni<F: FiniteField> {
+    pub coefficients : Vec<F>
 }
 
-impl<'a> Uni<'a>{
-    fn from(coefficients : Vec<FieldElement<'a>>) -> Uni<'a>{
+impl<F: FiniteField> Uni<F>{
+    pub fn from(coefficients : Vec<F>) -> Uni<F>{
         Uni{
             coefficients
         }
     }
 
-    fn degree(self) -> i128 {
-        if self.coefficients.len() == 0 {
-            return -1;
-        } 
-        let mut zero = self.coefficients[0].field.zero();
-        let _next_zero = zero.clone();
-        for coefficient in &self.coefficients {
-            zero = zero + *coefficient;
-        }
-
-        if zero.value == 0_i128 {
-            return - 1;
-        }
-
-        let mut max_index = 0_i128;
-        let mut counter = 0;
-        for i in self.coefficients {
-            if i != _next_zero {
-                max_index = counter;
-            }
-            counter = counter + 1;
+    pub fn degree(self) -> i128 {
+        // the zero polynomial has degree -1
+        match self.coefficients.iter().rposition(|c| !c.is_zero()) {
+            Some(index) => index as i128,
+            None => -1,
         }
-        return max_index;
     }
 
-    fn neg(&self) -> Uni<'a>{
-        let neg_coefficients: Vec<FieldElement<'a>> = self.coefficients.iter()
-            .map(|coeff| -coeff.clone())
+    fn neg(&self) -> Uni<F>{
+        let neg_coefficients: Vec<F> = self.coefficients.iter()
+            .map(|coeff| -*coeff)
             .collect();
         Uni::from(neg_coefficients)
     }
 
-    fn is_zero(&self) -> bool{
+    pub fn is_zero(&self) -> bool{
         // use degree
         self.clone().degree() == -1
     }
 
-    fn leading_coefficient(self) -> FieldElement<'a>{
+    pub fn leading_coefficient(self) -> F{
         if self.is_zero(){
-            return self.coefficients[0].field.zero();
+            return F::zero();
         }
         self.coefficients[self.clone().degree() as usize]
     }
 
-    fn true_division(self, rhs: Self) -> (Uni<'a>, Uni<'a>){
-        let mut quotient_coefficients: Vec<FieldElement<'a>> = vec![];
-        let mut remainder = self.clone();
-        let mut divisor = rhs.clone();
-        while !remainder.is_zero() && remainder.clone().degree() >= divisor.clone().degree(){
-            let leading_coefficient = remainder.clone().leading_coefficient() / divisor.clone().leading_coefficient();
-            let degree_difference = remainder.clone().degree() - divisor.clone().degree();
-            let mut term_coefficients: Vec<FieldElement<'a>> = vec![leading_coefficient];
-            for _ in 0..degree_difference{
-                term_coefficients.push(remainder.coefficients[0].field.zero());
+    /// Long division, returning `(quotient, remainder)`. Fails instead of
+    /// panicking when `rhs` is the zero polynomial.
+    pub fn true_division(self, rhs: Self) -> Result<(Uni<F>, Uni<F>), FieldError>{
+        if rhs.is_zero(){
+            return Err(FieldError::DivisionByZero);
+        }
+        let divisor_degree = rhs.clone().degree() as usize;
+        let leading_inverse = rhs.coefficients[divisor_degree].checked_inverse()?;
+        let numerator_degree = self.clone().degree();
+        if numerator_degree < divisor_degree as i128{
+            return Ok((Uni::from(vec![]), self));
+        }
+        let numerator_degree = numerator_degree as usize;
+
+        let mut remainder = self.coefficients;
+        let mut quotient_coefficients = vec![F::zero(); numerator_degree - divisor_degree + 1];
+        for i in (0..quotient_coefficients.len()).rev(){
+            let coefficient = remainder[i + divisor_degree] * leading_inverse;
+            quotient_coefficients[i] = coefficient;
+            for j in 0..=divisor_degree{
+                remainder[i + j] = remainder[i + j] - coefficient * rhs.coefficients[j];
             }
-            let term = Uni::from(term_coefficients);
-            quotient_coefficients.push(leading_coefficient);
-            remainder = remainder - (divisor.clone() * term);
         }
-        (Uni::from(quotient_coefficients), remainder)
+        remainder.truncate(divisor_degree);
+        Ok((Uni::from(quotient_coefficients), Uni::from(remainder)))
     }
 
-    fn evaluate(self, x: FieldElement<'a>) -> FieldElement<'a>{
-        let mut result = self.coefficients[0].clone();
-        let mut x_power = x.clone();
+    pub fn checked_div(self, rhs: Self) -> Result<Uni<F>, FieldError>{
+        Ok(self.true_division(rhs)?.0)
+    }
+
+    pub fn checked_rem(self, rhs: Self) -> Result<Uni<F>, FieldError>{
+        Ok(self.true_division(rhs)?.1)
+    }
+
+    pub fn evaluate(self, x: F) -> F{
+        let mut result = self.coefficients[0];
+        let mut x_power = x;
         for i in 1..self.coefficients.len(){
-            result = result + (self.coefficients[i].clone() * x_power.clone());
-            x_power = x_power * x.clone();
+            result = result + (self.coefficients[i] * x_power);
+            x_power = x_power * x;
         }
         result
     }
 
-    fn evaluate_domain(self, domain: Vec<FieldElement<'a>>) -> Vec<FieldElement<'a>>{
-        domain.iter().map(|x| self.clone().evaluate(x.clone())).collect()
+    pub fn evaluate_domain(self, domain: Vec<F>) -> Vec<F>{
+        if domain.len().min(self.coefficients.len()) >= TREE_EVALUATION_THRESHOLD{
+            return SubproductTree::new(&domain).evaluate(&self);
+        }
+        // Horner's rule at every point at once, on packed slices
+        let mut values = vec![F::zero(); domain.len()];
+        for &coefficient in self.coefficients.iter().rev(){
+            packed::mul_assign(&mut values, &domain);
+            packed::add_scalar_assign(&mut values, coefficient);
+        }
+        values
     }
 
-    fn interpolate_domain(domain: Vec<FieldElement<'a>>, values: Vec<FieldElement<'a>>) -> Uni<'a>{
-        let mut result = Uni::from(vec![]);
-        for i in 0..domain.len(){
-            let mut term_coefficients: Vec<FieldElement<'a>> = vec![values[i].clone()];
-            for j in 0..domain.len(){
-                if i != j{
-                    term_coefficients = vec![
-                        term_coefficients[0] * domain[j].clone() * domain[j].clone() / (domain[j].clone() - domain[i].clone())
-                    ];
-                }
-            }
-            result = result + Uni::from(term_coefficients);
+    /// Evaluates at `1, generator, ..., generator^(size - 1)` with an NTT,
+    /// where `generator` has order `size`, a power of two. Coefficients of
+    /// `x^size` and above wrap around, as `generator^size = 1`.
+    pub fn evaluate_on_subgroup(&self, generator: F, size: usize) -> Vec<F>{
+        // checks size and generator before the fold divides by size
+        let twiddles = Twiddles::new(generator, size);
+        let mut values = vec![F::zero(); size];
+        for (i, coefficient) in self.coefficients.iter().enumerate(){
+            values[i % size] = values[i % size] + *coefficient;
         }
-        result
+        twiddles.ntt(&mut values);
+        values
     }
 
-    fn zerofier_domain(domain: Vec<FieldElement<'a>>) -> Uni<'a>{
-        let mut result = Uni::from(vec![]);
-        for i in 0..domain.len(){
-            let mut term_coefficients: Vec<FieldElement<'a>> = vec![domain[i].field.zero()];
-            for j in 0..domain.len(){
-                if i != j{
-                    term_coefficients = vec![
-                        term_coefficients[0] * domain[j].clone() / (domain[j].clone() - domain[i].clone())
-                    ];
-                }
-            }
-            result = result + Uni::from(term_coefficients);
+    /// The polynomial of degree below `values.len()` taking `values[i]` at
+    /// `generator^i`, by an inverse NTT; `generator` must have order
+    /// `values.len()`, a power of two.
+    pub fn interpolate_subgroup(generator: F, values: Vec<F>) -> Uni<F>{
+        let mut coefficients = values;
+        Twiddles::new(generator, coefficients.len()).intt(&mut coefficients);
+        Uni::from(coefficients)
+    }
+
+    /// Evaluates at `offset * generator^i` for `i < size`; see
+    /// `evaluate_on_subgroup`.
+    pub fn evaluate_on_coset(&self, offset: F, generator: F, size: usize) -> Vec<F>{
+        self.dilate(offset).evaluate_on_subgroup(generator, size)
+    }
+
+    /// The polynomial taking `values[i]` at `offset * generator^i`; see
+    /// `interpolate_subgroup`.
+    pub fn interpolate_coset(offset: F, generator: F, values: Vec<F>) -> Uni<F>{
+        // q(x) = p(offset * x) is the interpolant on the subgroup
+        Uni::interpolate_subgroup(generator, values).dilate(offset.inverse())
+    }
+
+    /// `p(factor * x)`: the `i`-th coefficient times `factor^i`.
+    fn dilate(&self, factor: F) -> Uni<F>{
+        Uni::from(self.coefficients.iter().zip(factor.powers()).map(|(c, f)| *c * f).collect())
+    }
+
+    /// The polynomial of degree below `domain.len()` taking `values[i]` at
+    /// `domain[i]`, for any distinct points. To interpolate many value sets
+    /// on one domain, or evaluate without coefficients, build a
+    /// `BarycentricDomain` or `SubproductTree` once instead.
+    pub fn interpolate_domain(domain: Vec<F>, values: Vec<F>) -> Result<Uni<F>, FieldError>{
+        if domain.len() >= TREE_INTERPOLATION_THRESHOLD{
+            return SubproductTree::new(&domain).interpolate(&values);
         }
-        result
+        BarycentricDomain::new(domain)?.interpolate(&values)
+    }
+
+    /// The monic polynomial `prod_i (x - domain[i])`, which vanishes exactly
+    /// on the domain.
+    pub fn zerofier_domain(domain: Vec<F>) -> Result<Uni<F>, FieldError>{
+        check_distinct(&domain)?;
+        if domain.len() >= TREE_INTERPOLATION_THRESHOLD{
+            return Ok(SubproductTree::new(&domain).zerofier());
+        }
+        Ok(Uni::from(zerofier(&domain)))
     }
 
-    fn scale(self, scalar: FieldElement<'a>) -> Uni<'a>{
-        let scaled_coefficients: Vec<FieldElement<'a>> = self.coefficients.iter()
-            .map(|coeff| coeff.clone() * scalar.clone())
+    /// A random polynomial of exactly `degree`: coefficients are uniform,
+    /// except the leading one, which is uniform among non-zero elements.
+    #[cfg(feature = "rand")]
+    pub fn random<R: rand::RngCore + ?Sized>(degree: usize, rng: &mut R) -> Uni<F>{
+        let mut coefficients: Vec<F> = (0..degree).map(|_| F::random(rng)).collect();
+        let leading_coefficient = loop {
+            let candidate = F::random(rng);
+            if !candidate.is_zero(){
+                break candidate;
+            }
+        };
+        coefficients.push(leading_coefficient);
+        Uni::from(coefficients)
+    }
+
+    pub fn scale(self, scalar: F) -> Uni<F>{
+        let scaled_coefficients: Vec<F> = self.coefficients.iter()
+            .map(|coeff| *coeff * scalar)
             .collect();
         Uni::from(scaled_coefficients)
     }
 
-    fn test_colinearity(points : Vec<(FieldElement<'a>, FieldElement<'a>)>) -> bool{
+    pub fn test_colinearity(points : Vec<(F, F)>) -> bool{
         let mut result = true;
         for i in 0..points.len(){
             for j in 0..points.len(){
                 if i != j{
-                    let x_diff = points[i].0.clone() - points[j].0.clone();
-                    let y_diff = points[i].1.clone() - points[j].1.clone();
-                    if x_diff.clone().value == 0 || y_diff.clone().value == 0{
+                    let x_diff = points[i].0 - points[j].0;
+                    let y_diff = points[i].1 - points[j].1;
+                    if x_diff.is_zero() || y_diff.is_zero(){
                         result = false;
                     } else {
-                        let slope = y_diff.clone() / x_diff.clone();
+                        let slope = match y_diff.checked_div(x_diff){
+                            Ok(slope) => slope,
+                            Err(_) => return false,
+                        };
                         for k in 0..points.len(){
                             if k != i && k != j{
-                                let x_diff_2 = points[i].0.clone() - points[k].0.clone();
-                                let y_diff_2 = points[i].1.clone() - points[k].1.clone();
-                                if x_diff_2.clone().value == 0 || y_diff_2.clone().value == 0{
+                                let x_diff_2 = points[i].0 - points[k].0;
+                                let y_diff_2 = points[i].1 - points[k].1;
+                                if x_diff_2.is_zero() || y_diff_2.is_zero(){
                                     result = false;
                                 } else {
-                                    let slope_2 = y_diff_2.clone() / x_diff_2.clone();
+                                    let slope_2 = match y_diff_2.checked_div(x_diff_2){
+                                        Ok(slope) => slope,
+                                        Err(_) => return false,
+                                    };
                                     if slope != slope_2{
                                         result = false;
                                     }
@@ -166,24 +233,40 @@ impl<'a> Uni<'a>{
         result
     }
 
-    fn lift(univariate_poly : Uni<'a>, variable_index : usize) -> MPolynomial<'a>{
+    /// The same polynomial as a multivariate one in the variable `variable_index`.
+    pub fn lift(univariate_poly : Uni<F>, variable_index : usize) -> MPolynomial<F>{
         let mut dictionary = HashMap::new();
-        for i in 0..univariate_poly.coefficients.len(){
-            let mut exponents = vec![0; univariate_poly.coefficients.len()];
-            exponents[i] = 1;
-            dictionary.insert(Exponents(exponents), univariate_poly.coefficients[i].clone());
+        for (i, coefficient) in univariate_poly.coefficients.into_iter().enumerate(){
+            let mut exponents = vec![0; variable_index + 1];
+            exponents[variable_index] = i as i32;
+            dictionary.insert(Exponents(exponents), coefficient);
         }
         MPolynomial::new(dictionary)
     }
+}
 
-    
+impl<F: FftField> Uni<F>{
+    /// Low-degree extension: from the values of a polynomial of degree below
+    /// `n = values.len()` on the subgroup generated by
+    /// `F::primitive_nth_root(n)`, its values on the coset `offset * K` with
+    /// `|K| = blowup * n`, at `offset * omega^i` for
+    /// `omega = F::primitive_nth_root(blowup * n)`. That is the
+    /// `Fri::eval_domain` of a FRI built from `offset` and `omega`, so the
+    /// result can go straight to `Fri::prove`. Both `n` and `blowup` must be
+    /// powers of two.
+    pub fn low_degree_extend(values: Vec<F>, blowup: usize, offset: F) -> Vec<F>{
+        let n = values.len();
+        let extended_size = n * blowup;
+        let poly = Uni::interpolate_subgroup(F::primitive_nth_root(n as u64), values);
+        poly.evaluate_on_coset(offset, F::primitive_nth_root(extended_size as u64), extended_size)
+    }
 }
 
-impl<'a> Add for Uni<'a>{
-    type Output = Uni<'a>;
+impl<F: FiniteField> Add for Uni<F>{
+    type Output = Uni<F>;
 
-    fn add(self, rhs: Self) -> Uni<'a> {
-        let mut sum_coefficients: Vec<FieldElement<'a>> = vec![];
+    fn add(self, rhs: Self) -> Uni<F> {
+        let mut sum_coefficients: Vec<F> = vec![];
         let mut i = 0;
         let mut j = 0;
         while i < self.coefficients.len() && j < rhs.coefficients.len(){
@@ -205,101 +288,80 @@ impl<'a> Add for Uni<'a>{
         Uni::from(sum_coefficients)
     }
 }
-impl<'a> PartialEq for Uni<'a> {
+impl<F: FiniteField> PartialEq for Uni<F> {
     fn eq(&self, other: &Self) -> bool {
         self.coefficients == other.coefficients
     }
 }
 
-impl<'a> Sub for Uni<'a>{
-    type Output = Uni<'a>;
+/// Highest degree first, e.g. "3x^2 + 2x + 1"; `{:#}` prints signed
+/// coefficients, e.g. "x - 1" instead of "x + 18446744069414584320".
+impl<F: FiniteField + fmt::Display> fmt::Display for Uni<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let terms = self.coefficients.iter().enumerate().rev().map(|(i, &coefficient)| {
+            let monomial = match i {
+                0 => String::new(),
+                1 => "x".to_string(),
+                _ => format!("x^{}", i),
+            };
+            (coefficient, monomial)
+        });
+        write_terms(f, terms)
+    }
+}
+
+impl<F: FiniteField> Sub for Uni<F>{
+    type Output = Uni<F>;
 
-    fn sub(self, rhs: Self) -> Uni<'a> {
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn sub(self, rhs: Self) -> Uni<F> {
         let neg_rhs = rhs.neg();
         self + neg_rhs
     }
 }
 
-impl<'a> Mul for Uni<'a>{
-    type Output = Uni<'a>;
-
-    fn mul(self, rhs: Self) -> Uni<'a> {
-        let mut product_coefficients: Vec<FieldElement<'a>> = vec![];
-        for i in 0..self.coefficients.len(){
-            for j in 0..rhs.coefficients.len(){
-                let product = self.coefficients[i] * rhs.coefficients[j];
-                let index = i + j;
-                if index >= product_coefficients.len(){
-                    product_coefficients.push(product);
-                } else {
-                    product_coefficients[index] = product_coefficients[index] + product;
-                }
-            }
-        }
-        Uni::from(product_coefficients)
+impl<F: FiniteField> Mul for Uni<F>{
+    type Output = Uni<F>;
+
+    /// Schoolbook, Karatsuba or NTT depending on the sizes; see
+    /// `multiply::multiply`.
+    fn mul(self, rhs: Self) -> Uni<F> {
+        Uni::from(multiply(&self.coefficients, &rhs.coefficients))
     }
 }
 
 
 
-impl<'a> Div for Uni<'a>{
-    type Output = Uni<'a>;
+impl<F: FiniteField> Div for Uni<F>{
+    type Output = Uni<F>;
 
-    fn div(self, rhs: Self) -> Uni<'a> {
-        let mut quotient_coefficients: Vec<FieldElement<'a>> = vec![];
-        let mut remainder = self.clone();
-        let mut divisor = rhs.clone();
-        while !remainder.is_zero() && remainder.clone().degree() >= divisor.clone().degree(){
-            let leading_coefficient = remainder.clone().leading_coefficient() / divisor.clone().leading_coefficient();
-            let degree_difference = remainder.clone().degree() - divisor.clone().degree();
-            let mut term_coefficients: Vec<FieldElement<'a>> = vec![leading_coefficient];
-            for _ in 0..degree_difference{
-                term_coefficients.push(remainder.coefficients[0].field.zero());
-            }
-            let term = Uni::from(term_coefficients);
-            quotient_coefficients.push(leading_coefficient);
-            remainder = remainder - (divisor.clone() * term);
-        }
-        Uni::from(quotient_coefficients)
+    fn div(self, rhs: Self) -> Uni<F> {
+        self.checked_div(rhs).expect("division by the zero polynomial")
     }
 }
 
-impl<'a> Rem for Uni<'a>{
-    type Output = Uni<'a>;
-
-    fn rem(self, rhs: Self) -> Uni<'a> {
-        let mut quotient_coefficients: Vec<FieldElement<'a>> = vec![];
-        let mut remainder = self.clone();
-        let mut divisor = rhs.clone();
-        while !remainder.is_zero() && remainder.clone().degree() >= divisor.clone().degree(){
-            let leading_coefficient = remainder.clone().leading_coefficient() / divisor.clone().leading_coefficient();
-            let degree_difference = remainder.clone().degree() - divisor.clone().degree();
-            let mut term_coefficients: Vec<FieldElement<'a>> = vec![leading_coefficient];
-            for _ in 0..degree_difference{
-                term_coefficients.push(remainder.coefficients[0].field.zero());
-            }
-            let term = Uni::from(term_coefficients);
-            quotient_coefficients.push(leading_coefficient);
-            remainder = remainder - (divisor.clone() * term);
-        }
-        remainder
+impl<F: FiniteField> Rem for Uni<F>{
+    type Output = Uni<F>;
+
+    fn rem(self, rhs: Self) -> Uni<F> {
+        self.checked_rem(rhs).expect("division by the zero polynomial")
     }
 }
 
 // implement the xor bit symbol for exponentiation of the polynomial
-impl<'a> BitXor for Uni<'a>{
-    type Output = Uni<'a>;
+impl<F: FiniteField> BitXor for Uni<F>{
+    type Output = Uni<F>;
 
-    fn bitxor(self, rhs: Self) -> Uni<'a> {
-        let mut result = Uni::from(vec![self.coefficients[0].field.one()]);
+    fn bitxor(self, rhs: Self) -> Uni<F> {
+        let mut result = Uni::from(vec![F::one()]);
         let mut base = self.clone();
         let mut exponent = rhs.clone();
         while !exponent.is_zero(){
-            if exponent.clone().leading_coefficient() == exponent.coefficients[0].field.one(){
+            if exponent.clone().leading_coefficient() == F::one(){
                 result = result * base.clone();
             }
             base = base.clone() * base.clone();
-            exponent = exponent.clone() - Uni::from(vec![exponent.coefficients[0].field.one()]);
+            exponent = exponent.clone() - Uni::from(vec![F::one()]);
         }
         result
     }
@@ -307,7 +369,8 @@ impl<'a> BitXor for Uni<'a>{
 
 #[cfg(test)]
 mod test{
-    use crate::field::field::Field;
+    use crate::field::{extension::Fp2, field::Field, field_element::{FieldElement, Goldilocks}, monty31::BabyBear, powers::coset, traits::PrimeField, DEFAULT_PRIME};
+    use crate::poly::barycentric::zerofier as zerofier_of;
 
     use super::*;
 
@@ -324,9 +387,8 @@ mod test{
     }
 
     #[test]
-    fn get_polynomial_degree__1(){
-        let field = Field::new();
-        let coefficients = vec![];
+    fn get_polynomial_degree_of_zero(){
+        let coefficients: Vec<FieldElement> = vec![];
         let uni = Uni::from(coefficients);
         assert_eq!(uni.degree(), -1);
     }
@@ -386,6 +448,186 @@ mod test{
         ]));
     }
 
+    #[test]
+    fn polynomial_over_other_prime(){
+        let field = Field::<97>;
+        let uni = Uni::from(vec![
+            FieldElement::from(96, &field),
+            FieldElement::from(50, &field),
+        ]);
+
+        let uni_sum = uni.clone() + uni;
+
+        assert_eq!(uni_sum, Uni::from(vec![
+            FieldElement::from(95, &field),
+            FieldElement::from(3, &field),
+        ]));
+    }
+
+    #[test]
+    fn polynomial_degree_ignores_trailing_zeros(){
+        let field = Field::new();
+        // x - 1: coefficients sum to zero but the polynomial isn't zero
+        let uni = Uni::from(vec![
+            FieldElement::from(-1, &field),
+            FieldElement::from(1, &field),
+            FieldElement::from(0, &field),
+        ]);
+        assert_eq!(uni.degree(), 1);
+        assert!(Uni::from(vec![field.zero(), field.zero()]).is_zero());
+    }
+
+    #[test]
+    fn polynomial_division(){
+        let field = Field::new();
+        // (x^2 + 3x + 5) = (x + 1)(x + 2) + 3
+        let numerator = Uni::from(vec![
+            FieldElement::from(5, &field),
+            FieldElement::from(3, &field),
+            FieldElement::from(1, &field),
+        ]);
+        let divisor = Uni::from(vec![
+            FieldElement::from(1, &field),
+            FieldElement::from(1, &field),
+        ]);
+        let (quotient, remainder) = numerator.clone().true_division(divisor.clone()).unwrap();
+        assert_eq!(quotient, Uni::from(vec![
+            FieldElement::from(2, &field),
+            FieldElement::from(1, &field),
+        ]));
+        assert_eq!(remainder, Uni::from(vec![FieldElement::from(3, &field)]));
+        assert_eq!(numerator.clone() / divisor.clone(), quotient);
+        assert_eq!(numerator % divisor, remainder);
+    }
+
+    #[test]
+    fn polynomial_division_by_zero(){
+        let field = Field::new();
+        let numerator = Uni::from(vec![FieldElement::from(5, &field)]);
+        let zero = Uni::from(vec![field.zero()]);
+        assert_eq!(numerator.clone().checked_div(zero.clone()), Err(FieldError::DivisionByZero));
+        assert_eq!(numerator.checked_rem(zero), Err(FieldError::DivisionByZero));
+    }
+
+    #[test]
+    fn polynomial_serde_roundtrip(){
+        let field = Field::new();
+        let uni = Uni::from(vec![
+            FieldElement::from(-1, &field),
+            FieldElement::from(7, &field),
+        ]);
+        let bytes = serde_pickle::to_vec(&uni, Default::default()).unwrap();
+        let decoded: Uni<FieldElement> = serde_pickle::from_slice(&bytes, Default::default()).unwrap();
+        assert_eq!(decoded, uni);
+
+        let extension = Uni::from(Fp2::<Goldilocks>::sample_many(b"coefficients", 5));
+        let bytes = serde_pickle::to_vec(&extension, Default::default()).unwrap();
+        assert_eq!(serde_pickle::from_slice::<Uni<Fp2<Goldilocks>>>(&bytes, Default::default()).unwrap(), extension);
+
+        // a coefficient equal to p is not reduced
+        let reduced = serde_pickle::to_vec(&vec![1u64.to_le_bytes(), (DEFAULT_PRIME - 1).to_le_bytes()], Default::default()).unwrap();
+        assert_eq!(serde_pickle::from_slice::<Uni<Goldilocks>>(&reduced, Default::default()).unwrap(), Uni::from(vec![Goldilocks::one(), -Goldilocks::one()]));
+        let unreduced = serde_pickle::to_vec(&vec![1u64.to_le_bytes(), DEFAULT_PRIME.to_le_bytes()], Default::default()).unwrap();
+        assert!(serde_pickle::from_slice::<Uni<Goldilocks>>(&unreduced, Default::default()).is_err());
+    }
+
+    #[test]
+    fn polynomial_display(){
+        let field = Field::new();
+        let uni = Uni::from(vec![
+            FieldElement::from(1, &field),
+            FieldElement::from(2, &field),
+            FieldElement::from(3, &field),
+        ]);
+        assert_eq!(uni.to_string(), "3x^2 + 2x + 1");
+
+        let uni = Uni::from(vec![
+            FieldElement::from(-1, &field),
+            FieldElement::from(0, &field),
+            FieldElement::from(1, &field),
+            FieldElement::from(-5, &field),
+        ]);
+        assert_eq!(format!("{:#}", uni), "-5x^3 + x^2 - 1");
+        assert_eq!(uni.to_string(), "18446744069414584316x^3 + x^2 + 18446744069414584320");
+        assert_eq!(Uni::<FieldElement>::from(vec![]).to_string(), "0");
+        assert_eq!(Uni::from(vec![field.zero(), field.one()]).to_string(), "x");
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn random_polynomial(){
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(0);
+        for degree in 0..8{
+            let uni: Uni<FieldElement<5>> = Uni::random(degree, &mut rng);
+            assert_eq!(uni.degree(), degree as i128);
+        }
+    }
+
+    #[test]
+    fn evaluate_domain_matches_evaluate(){
+        let field = Field::new();
+        let uni = Uni::from((1..=7).map(|i| FieldElement::from(-i * 1000003, &field)).collect());
+        let domain: Vec<FieldElement> = (0..21).map(|i| FieldElement::from(i * i - 5, &field)).collect();
+        let expected: Vec<FieldElement> = domain.iter().map(|&x| uni.clone().evaluate(x)).collect();
+        assert_eq!(uni.evaluate_domain(domain.clone()), expected);
+        assert_eq!(Uni::from(vec![]).evaluate_domain(domain), vec![field.zero(); 21]);
+    }
+
+    #[test]
+    fn evaluate_and_interpolate_on_subgroup(){
+        let omega = Goldilocks::primitive_nth_root(8);
+        let poly = Uni::from((1..=5).map(Goldilocks::new).collect());
+        let values = poly.evaluate_on_subgroup(omega, 8);
+        assert_eq!(values, poly.clone().evaluate_domain(omega.powers().take(8).collect()));
+        let mut expected = poly.coefficients.clone();
+        expected.resize(8, Goldilocks::zero());
+        assert_eq!(Uni::interpolate_subgroup(omega, values).coefficients, expected);
+
+        // x^4 wraps around to 1 on a subgroup of order 4
+        let omega = Goldilocks::primitive_nth_root(4);
+        let values = poly.evaluate_on_subgroup(omega, 4);
+        let folded = Uni::from(vec![Goldilocks::new(1 + 5), Goldilocks::new(2), Goldilocks::new(3), Goldilocks::new(4)]);
+        assert_eq!(values, folded.evaluate_domain(omega.powers().take(4).collect()));
+    }
+
+    #[test]
+    #[should_panic(expected = "NTT size must be a power of two, got 0")]
+    fn evaluate_on_empty_subgroup(){
+        Uni::from(vec![Goldilocks::one()]).evaluate_on_subgroup(Goldilocks::one(), 0);
+    }
+
+    #[test]
+    fn large_multiplication(){
+        let a = Uni::from(Goldilocks::new(3).powers().take(1 << 12).collect());
+        let b = Uni::from(Goldilocks::new(5).powers().take(3000).collect());
+        let x = Goldilocks::new(123456789);
+        let product = a.clone() * b.clone();
+        assert_eq!(product.clone().degree(), (1 << 12) + 3000 - 2);
+        assert_eq!(product.evaluate(x), a.evaluate(x) * b.evaluate(x));
+    }
+
+    #[test]
+    fn coset_interpolation(){
+        let offset = Goldilocks::generator();
+        let omega = Goldilocks::primitive_nth_root(8);
+        let poly = Uni::from((1..=8).map(Goldilocks::new).collect());
+        let values = poly.evaluate_on_coset(offset, omega, 8);
+        assert_eq!(values, poly.clone().evaluate_domain(coset(offset, omega, 8).collect()));
+        assert_eq!(Uni::interpolate_coset(offset, omega, values), poly);
+    }
+
+    #[test]
+    fn low_degree_extend(){
+        let poly = Uni::from((1..=4).map(|i| BabyBear::new(i * 100)).collect());
+        let values = poly.evaluate_on_subgroup(BabyBear::primitive_nth_root(4), 4);
+        let offset = BabyBear::generator();
+        let extension = Uni::low_degree_extend(values, 8, offset);
+        let domain: Vec<BabyBear> = coset(offset, BabyBear::primitive_nth_root(32), 32).collect();
+        assert_eq!(extension, poly.evaluate_domain(domain));
+    }
+
     #[test]
     fn interpolate_domain(){
         let field = Field::new();
@@ -401,12 +643,49 @@ mod test{
             FieldElement::from(9, &field),
         ];
 
-        let uni = Uni::interpolate_domain(domain, values);
-        dbg!(&uni);
+        let uni = Uni::interpolate_domain(domain, values).unwrap();
         assert_eq!(uni, Uni::from(vec![
-            FieldElement::from(1, &field),
             FieldElement::from(0, &field),
             FieldElement::from(0, &field),
+            FieldElement::from(1, &field),
         ]));
     }
+
+    #[test]
+    fn interpolate_domain_rejects_duplicates(){
+        let domain = vec![Goldilocks::new(1), Goldilocks::new(2), Goldilocks::new(1)];
+        let values = vec![Goldilocks::new(1), Goldilocks::new(4), Goldilocks::new(9)];
+        assert_eq!(Uni::interpolate_domain(domain.clone(), values).unwrap_err(), FieldError::DuplicatePoint);
+        assert_eq!(Uni::zerofier_domain(domain).unwrap_err(), FieldError::DuplicatePoint);
+    }
+
+    #[test]
+    fn large_domains_use_the_subproduct_tree(){
+        let domain: Vec<Goldilocks> = (0..2100u64).map(|i| Goldilocks::new(i * i + 1)).collect();
+        let poly = Uni::from(Goldilocks::new(7).powers().take(2100).collect());
+        let values = poly.clone().evaluate_domain(domain.clone());
+        assert_eq!(values[1234], poly.clone().evaluate(domain[1234]));
+
+        let (small_domain, small_values) = (domain[..300].to_vec(), values[..300].to_vec());
+        let interpolant = Uni::interpolate_domain(small_domain.clone(), small_values.clone()).unwrap();
+        assert_eq!(interpolant.clone().degree(), 299);
+        assert_eq!(interpolant.evaluate(small_domain[17]), small_values[17]);
+
+        let zerofier = Uni::zerofier_domain(small_domain.clone()).unwrap();
+        assert_eq!(zerofier, Uni::from(zerofier_of(&small_domain)));
+        let mut duplicated = small_domain;
+        duplicated[299] = duplicated[0];
+        assert_eq!(Uni::interpolate_domain(duplicated.clone(), small_values).unwrap_err(), FieldError::DuplicatePoint);
+        assert_eq!(Uni::zerofier_domain(duplicated).unwrap_err(), FieldError::DuplicatePoint);
+    }
+
+    #[test]
+    fn zerofier_domain(){
+        let domain: Vec<Goldilocks> = (1..=6).map(|i| Goldilocks::new(i * i + 3)).collect();
+        let zerofier = Uni::zerofier_domain(domain.clone()).unwrap();
+        assert_eq!(zerofier.clone().degree(), 6);
+        assert_eq!(zerofier.clone().leading_coefficient(), Goldilocks::one());
+        assert!(zerofier.clone().evaluate_domain(domain).iter().all(|y| y.is_zero()));
+        assert!(!zerofier.evaluate(Goldilocks::new(5)).is_zero());
+    }
 }
\ No newline at end of file